@@ -0,0 +1,134 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use crate::error::Error;
+
+pub const ANSWERS_PATH: &str = "resources/answers.toml";
+
+/// Known-good answers for the inputs in `resources/`.
+///
+/// The file is a small subset of TOML: a `[dayNN]` table per day with `part1` and `part2` keys.
+/// Values are basic strings (`"1532"`) or, for answers that span several lines like the one of
+/// day 13, multi-line literal strings between `'''`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    answers: HashMap<(u8, u8), String>,
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Answers, Error> {
+        std::fs::read_to_string(path)
+            .map_err(|e| Error::io(format!("{}: {}", path.display(), e)))?
+            .parse()
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|answer| answer.as_str())
+    }
+}
+
+impl FromStr for Answers {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = HashMap::new();
+        let mut day = None;
+        let mut lines = s.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let error = |message: String| Error::invalid(message).at_line(index + 1);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                day = match table.strip_prefix("day").map(str::parse) {
+                    Some(Ok(number @ 1..=25)) => Some(number),
+                    _ => {
+                        return Err(error(format!(
+                            "expected a table like [day01], got [{}]",
+                            table
+                        )))
+                    }
+                };
+                continue;
+            }
+            let day = day.ok_or_else(|| error("expected a [dayNN] table first".to_owned()))?;
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected 'key = value', got '{}'", line)))?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                other => return Err(error(format!("unknown key '{}'", other))),
+            };
+            let value = value.trim();
+            let answer = if value == "'''" {
+                // A multi-line literal string, the first newline is not part of it
+                let mut answer = Vec::new();
+                loop {
+                    match lines.next() {
+                        Some((_, line)) if line.trim_end() == "'''" => break,
+                        Some((_, line)) => answer.push(line),
+                        None => return Err(error("unterminated multi-line string".to_owned())),
+                    }
+                }
+                answer.join("\n")
+            } else {
+                value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .filter(|value| !value.contains('"') && !value.contains('\\'))
+                    .ok_or_else(|| error(format!("expected a quoted string, got {}", value)))?
+                    .to_owned()
+            };
+            if answers.insert((day, part), answer).is_some() {
+                return Err(error(format!(
+                    "duplicate answer for day {} part {}",
+                    day, part
+                )));
+            }
+        }
+        Ok(Answers { answers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_answers() {
+        let answers: Answers = "# comment
+        [day01]
+        part1 = \"1532\"
+
+        [day13]
+        part2 = '''
+#..#
+####
+'''"
+        .parse()
+        .unwrap();
+        assert_eq!(Some("1532"), answers.get(1, 1));
+        assert_eq!(None, answers.get(1, 2));
+        assert_eq!(Some("#..#\n####"), answers.get(13, 2));
+    }
+
+    #[test]
+    fn it_rejects_invalid_answers() {
+        let line = |s: &str| s.parse::<Answers>().unwrap_err().line;
+        assert_eq!(Some(1), line("part1 = \"1\""));
+        assert_eq!(Some(1), line("[day26]"));
+        assert_eq!(Some(2), line("[day01]\npart3 = \"1\""));
+        assert_eq!(Some(2), line("[day01]\npart1 = 1"));
+        assert_eq!(Some(3), line("[day01]\npart1 = \"1\"\npart1 = \"2\""));
+        assert_eq!(Some(2), line("[day13]\npart2 = '''\n#..#"));
+    }
+
+    #[test]
+    fn it_loads_answers_of_every_day() {
+        let answers = Answers::load(Path::new(ANSWERS_PATH)).unwrap();
+        for day in 1..=25 {
+            assert!(answers.get(day, 1).is_some(), "day {} is missing", day);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cli::{BenchOptions, Format},
+    error::Error,
+    input::InputSource,
+    registry::{get_days, Variant},
+    runner::select_variants,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Parse => write!(f, "parse"),
+            Step::Part1 => write!(f, "part1"),
+            Step::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// `None` if there are no samples. For an even number of samples, the median is the mean of
+    /// the two in the middle.
+    pub fn new(mut samples: Vec<Duration>) -> Option<Stats> {
+        samples.sort_unstable();
+        let middle = samples.len() / 2;
+        let median = match samples.len() {
+            0 => return None,
+            len if len % 2 == 0 => (samples[middle - 1] + samples[middle]) / 2,
+            _ => samples[middle],
+        };
+        Some(Stats {
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        })
+    }
+}
+
+/// The timings of one step of one variant, or why it could not be timed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub day: u8,
+    pub variant: &'static str,
+    pub step: Step,
+    pub iterations: usize,
+    pub result: Result<Stats, String>,
+}
+
+/// Times parsing and both parts of the selected days. Without a requested variant, all
+/// variants of a day are timed, so that they can be compared.
+pub fn bench(options: &BenchOptions) -> Result<(), String> {
+    let days = get_days();
+    let selected: Vec<(u8, &Variant)> = match &options.variant {
+        Some(variant) => select_variants(&days, &options.days, Some(variant))?,
+        None => days
+            .iter()
+            .filter(|day| options.days.contains(&day.day))
+            .flat_map(|day| day.variants.iter().map(move |variant| (day.day, variant)))
+            .collect(),
+    };
+    if selected.is_empty() {
+        return Err("No puzzle selected".to_owned());
+    }
+    print!("{}", header(options.format));
+    let mut first = true;
+    for (day, variant) in selected {
+        for measurement in bench_variant(day, variant, options.iterations) {
+            print!("{}", row(options.format, &measurement, first));
+            first = false;
+        }
+    }
+    print!("{}", footer(options.format));
+    Ok(())
+}
+
+fn bench_variant(day: u8, variant: &Variant, iterations: usize) -> Vec<Measurement> {
+    let measurement = |step, result: Result<Stats, Error>| Measurement {
+        day,
+        variant: variant.name,
+        step,
+        iterations,
+        result: result.map_err(|e| e.in_day(day).to_string()),
+    };
+    let mut parsed = None;
+    let parse = InputSource::Resources.read(day).and_then(|input| {
+        time(iterations, || {
+            parsed = Some(variant.solution.parse(&input)?);
+            Ok(())
+        })
+    });
+    let parse_failed = parse.is_err();
+    let mut measurements = vec![measurement(Step::Parse, parse)];
+    if let (false, Some(input)) = (parse_failed, parsed) {
+        let input = input.as_ref();
+        let part1 = time(iterations, || variant.solution.part1(input).map(|_| ()));
+        measurements.push(measurement(Step::Part1, part1));
+        let part2 = time(iterations, || variant.solution.part2(input).map(|_| ()));
+        measurements.push(measurement(Step::Part2, part2));
+    }
+    measurements
+}
+
+/// Runs `f` the given number of times, stopping at the first error.
+fn time(iterations: usize, mut f: impl FnMut() -> Result<(), Error>) -> Result<Stats, Error> {
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        samples.push(start.elapsed());
+    }
+    Stats::new(samples).ok_or_else(|| Error::unsupported("no iterations"))
+}
+
+fn header(format: Format) -> String {
+    match format {
+        Format::Text => format!(
+            "{:>3}  {:<12} {:<6} {:>10} {:>10} {:>10}\n",
+            "Day", "Variant", "Step", "Min", "Median", "Max"
+        ),
+        Format::Json => "[\n".to_owned(),
+        Format::Csv => "day,variant,step,iterations,min_ns,median_ns,max_ns,error\n".to_owned(),
+    }
+}
+
+/// Renders one measurement. `first` tells whether it is the first row after the header.
+fn row(format: Format, measurement: &Measurement, first: bool) -> String {
+    let Measurement {
+        day,
+        variant,
+        step,
+        iterations,
+        result,
+    } = measurement;
+    match (format, result) {
+        (Format::Text, Ok(stats)) => format!(
+            "{:>3}  {:<12} {:<6} {:>10} {:>10} {:>10}\n",
+            day,
+            variant,
+            step,
+            format!("{:.1?}", stats.min),
+            format!("{:.1?}", stats.median),
+            format!("{:.1?}", stats.max)
+        ),
+        (Format::Text, Err(e)) => format!("{:>3}  {:<12} {:<6} {}\n", day, variant, step, e),
+        (Format::Json, result) => {
+            let timings = match result {
+                Ok(stats) => format!(
+                    "\"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}",
+                    stats.min.as_nanos(),
+                    stats.median.as_nanos(),
+                    stats.max.as_nanos()
+                ),
+                Err(e) => format!("\"error\": {}", json_string(e)),
+            };
+            format!(
+                "{}  {{\"day\": {}, \"variant\": {}, \"step\": \"{}\", \"iterations\": {}, {}}}",
+                if first { "" } else { ",\n" },
+                day,
+                json_string(variant),
+                step,
+                iterations,
+                timings
+            )
+        }
+        (Format::Csv, Ok(stats)) => format!(
+            "{},{},{},{},{},{},{},\n",
+            day,
+            csv_field(variant),
+            step,
+            iterations,
+            stats.min.as_nanos(),
+            stats.median.as_nanos(),
+            stats.max.as_nanos()
+        ),
+        (Format::Csv, Err(e)) => format!(
+            "{},{},{},{},,,,{}\n",
+            day,
+            csv_field(variant),
+            step,
+            iterations,
+            csv_field(e)
+        ),
+    }
+}
+
+fn footer(format: Format) -> String {
+    match format {
+        Format::Json => "\n]\n".to_owned(),
+        Format::Text | Format::Csv => String::new(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quotes the field if it contains a separator, a quote or a line break
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn it_calculates_stats() {
+        let stats = Stats::new(vec![ms(5), ms(1), ms(3)]).unwrap();
+        assert_eq!((ms(1), ms(3), ms(5)), (stats.min, stats.median, stats.max));
+        assert_eq!(
+            ms(2),
+            Stats::new(vec![ms(4), ms(1), ms(3), ms(1)]).unwrap().median
+        );
+        assert_eq!(None, Stats::new(vec![]));
+    }
+
+    #[test]
+    fn it_renders_rows() {
+        let measurement = Measurement {
+            day: 6,
+            variant: "fast",
+            step: Step::Part1,
+            iterations: 3,
+            result: Ok(Stats::new(vec![ms(1), ms(2), ms(3)]).unwrap()),
+        };
+        assert_eq!(
+            "6,fast,part1,3,1000000,2000000,3000000,\n",
+            row(Format::Csv, &measurement, true)
+        );
+        assert_eq!(
+            "  {\"day\": 6, \"variant\": \"fast\", \"step\": \"part1\", \"iterations\": 3, \
+             \"min_ns\": 1000000, \"median_ns\": 2000000, \"max_ns\": 3000000}",
+            row(Format::Json, &measurement, true)
+        );
+        let failed = Measurement {
+            result: Err("not supported: \"naive\", sorry".to_owned()),
+            ..measurement
+        };
+        assert_eq!(
+            "6,fast,part1,3,,,,\"not supported: \"\"naive\"\", sorry\"\n",
+            row(Format::Csv, &failed, true)
+        );
+        assert!(row(Format::Json, &failed, false)
+            .ends_with("\"error\": \"not supported: \\\"naive\\\", sorry\"}"));
+    }
+}
@@ -0,0 +1,713 @@
+use std::{path::PathBuf, str::FromStr};
+
+use crate::{answers::ANSWERS_PATH, input::InputSource, solution::Part};
+
+pub const USAGE: &str = "Usage:
+    aoc2021 [run] [DAYS]... [--all] [--part 1|2] [--variant NAME] [--input PATH] [--jobs N]
+    aoc2021 verify [DAYS]... [--variant NAME] [--answers PATH] [--jobs N]
+    aoc2021 bench [DAYS]... [--variant NAME] [--iterations N] [--format text|json|csv]
+    aoc2021 generate [--rooms N] [--depth N] [--seed N] [--count N]
+    aoc2021 play DAY [--delay MS] [--paused] [--frames N] [--input PATH]
+    aoc2021 export DAY --output PATH [--frames N] [--scale N] [--delay MS] [--input PATH]
+    aoc2021 disassemble [HEX] [--input PATH]
+    aoc2021 list
+    aoc2021 help
+
+DAYS can be a single day (15), a range (1..=10 or 1..10) or a list (3,5,7).
+Without any days, all days are run. `list` shows the available variants per day.
+By default, the input is read from resources/dayNN.txt. With a single day, --input
+reads it from PATH instead, or from stdin if PATH is `-`.
+--jobs solves up to N days at the same time, the answers are still printed in day order.
+`verify` compares the answers to the ones in resources/answers.toml, or in PATH.
+`bench` times parsing and both parts N times (default 5) and reports min, median and max.
+Without --variant, all variants of a day are timed side by side.
+`generate` prints random day 23 burrows (default: 4 rooms of depth 2) with their optimal energy,
+for the seeds from N on.
+`play` animates the simulation of day 5, 11, 13, 20, 23 or 25 in the terminal, waiting MS
+milliseconds (default 100) between frames. Type Enter to pause or resume, `s` to step,
+`+` or `-` to change the speed and `q` to quit, each followed by Enter.
+`export` draws the grids of day 9, 11, 15, 20 or 25 with N pixels per cell (default 4). PATH
+ending in .gif gets an animation of up to N frames (default 100), .png or .ppm a single image,
+or one numbered file per frame.
+`disassemble` lists the packets of a BITS transmission (day 16) with their bit offsets, and
+prints it as an expression. Without HEX, the transmission is read like the input of day 16.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(RunOptions),
+    Verify(VerifyOptions),
+    Bench(BenchOptions),
+    Generate(GenerateOptions),
+    Play(PlayOptions),
+    Export(ExportOptions),
+    Disassemble(DisassembleOptions),
+    List,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunOptions {
+    pub days: Vec<u8>,
+    pub part: Part,
+    pub variant: Option<String>,
+    pub input: InputSource,
+    pub jobs: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyOptions {
+    pub days: Vec<u8>,
+    pub variant: Option<String>,
+    pub answers: PathBuf,
+    pub jobs: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchOptions {
+    pub days: Vec<u8>,
+    pub variant: Option<String>,
+    pub iterations: usize,
+    pub format: Format,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateOptions {
+    pub rooms: u8,
+    pub depth: u8,
+    pub seed: u64,
+    pub count: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PlayOptions {
+    pub day: u8,
+    pub input: InputSource,
+    /// Milliseconds between two frames
+    pub delay: u64,
+    pub paused: bool,
+    pub frames: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportOptions {
+    pub day: u8,
+    pub input: InputSource,
+    pub output: PathBuf,
+    pub frames: usize,
+    /// Pixels per cell
+    pub scale: usize,
+    /// Milliseconds between two frames of an animation
+    pub delay: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DisassembleOptions {
+    /// The hexadecimal transmission, read from the input when missing
+    pub message: Option<String>,
+    pub input: InputSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(|arg| arg.as_str()) {
+        Some("list") => return Ok(Command::List),
+        Some("help" | "--help" | "-h") => return Ok(Command::Help),
+        Some("verify") => {
+            args.next();
+            return parse_verify_args(args);
+        }
+        Some("bench") => {
+            args.next();
+            return parse_bench_args(args);
+        }
+        Some("generate") => {
+            args.next();
+            return parse_generate_args(args);
+        }
+        Some("play") => {
+            args.next();
+            return parse_play_args(args);
+        }
+        Some("export") => {
+            args.next();
+            return parse_export_args(args);
+        }
+        Some("disassemble") => {
+            args.next();
+            return parse_disassemble_args(args);
+        }
+        Some("run") => {
+            args.next();
+        }
+        _ => {}
+    }
+    let mut days: Vec<u8> = Vec::new();
+    let mut all = false;
+    let mut part = Part::Both;
+    let mut variant = None;
+    let mut input = InputSource::Resources;
+    let mut jobs = 1;
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        match flag {
+            "--all" | "-a" => all = true,
+            "--part" | "-p" => {
+                let value = value()?;
+                part = match value.as_str() {
+                    "1" => Part::One,
+                    "2" => Part::Two,
+                    _ => return Err(format!("Invalid part '{}', expected 1 or 2", value)),
+                };
+            }
+            "--variant" | "-v" => variant = Some(value()?),
+            "--input" | "-i" => input = value()?.as_str().into(),
+            "--jobs" | "-j" => jobs = parse_count("jobs", &value()?)?,
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ => days.extend(parse_days(&arg)?),
+        }
+    }
+    let days = select_days(days, all);
+    if input != InputSource::Resources && days.len() != 1 {
+        return Err("--input can only be used with a single day".to_owned());
+    }
+    Ok(Command::Run(RunOptions {
+        days,
+        part,
+        variant,
+        input,
+        jobs,
+    }))
+}
+
+fn parse_verify_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut days: Vec<u8> = Vec::new();
+    let mut all = false;
+    let mut variant = None;
+    let mut answers = PathBuf::from(ANSWERS_PATH);
+    let mut jobs = 1;
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        match flag {
+            "--all" | "-a" => all = true,
+            "--variant" | "-v" => variant = Some(value()?),
+            "--answers" => answers = value()?.into(),
+            "--jobs" | "-j" => jobs = parse_count("jobs", &value()?)?,
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ => days.extend(parse_days(&arg)?),
+        }
+    }
+    Ok(Command::Verify(VerifyOptions {
+        days: select_days(days, all),
+        variant,
+        answers,
+        jobs,
+    }))
+}
+
+fn parse_bench_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut days: Vec<u8> = Vec::new();
+    let mut all = false;
+    let mut variant = None;
+    let mut iterations = 5;
+    let mut format = Format::Text;
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        match flag {
+            "--all" | "-a" => all = true,
+            "--variant" | "-v" => variant = Some(value()?),
+            "--iterations" | "-n" => iterations = parse_count("iterations", &value()?)?,
+            "--format" | "-f" => {
+                let value = value()?;
+                format = match value.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    _ => {
+                        return Err(format!(
+                            "Invalid format '{}', expected text, json or csv",
+                            value
+                        ))
+                    }
+                };
+            }
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ => days.extend(parse_days(&arg)?),
+        }
+    }
+    Ok(Command::Bench(BenchOptions {
+        days: select_days(days, all),
+        variant,
+        iterations,
+        format,
+    }))
+}
+
+fn parse_generate_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = GenerateOptions {
+        rooms: 4,
+        depth: 2,
+        seed: 0,
+        count: 1,
+    };
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        let small = |name, value: String| {
+            u8::try_from(parse_count(name, &value)?).map_err(|_| {
+                format!(
+                    "Invalid number of {} '{}', expected at most 255",
+                    name, value
+                )
+            })
+        };
+        match flag {
+            "--rooms" | "-r" => options.rooms = small("rooms", value()?)?,
+            "--depth" | "-d" => options.depth = small("depth", value()?)?,
+            "--seed" | "-s" => options.seed = parse_number("seed", &value()?, "a number")?,
+            "--count" | "-n" => options.count = parse_count("puzzles", &value()?)?,
+            _ => return Err(format!("Unknown option '{}'", arg)),
+        }
+    }
+    Ok(Command::Generate(options))
+}
+
+fn parse_play_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut input = InputSource::Resources;
+    let mut delay = 100;
+    let mut paused = false;
+    let mut frames = None;
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        match flag {
+            "--delay" | "-d" => delay = parse_number("delay", &value()?, "milliseconds")?,
+            "--paused" | "-p" => paused = true,
+            "--frames" | "-f" => frames = Some(parse_count("frames", &value()?)?),
+            "--input" | "-i" => input = value()?.as_str().into(),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ if day.is_some() => return Err("`play` takes a single day".to_owned()),
+            _ => day = Some(parse_day(&arg)?),
+        }
+    }
+    Ok(Command::Play(PlayOptions {
+        day: day.ok_or("`play` needs a day")?,
+        input,
+        delay,
+        paused,
+        frames,
+    }))
+}
+
+fn parse_export_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut input = InputSource::Resources;
+    let mut output = None;
+    let mut frames = 100;
+    let mut scale = 4;
+    let mut delay = 100;
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        match flag {
+            "--output" | "-o" => output = Some(PathBuf::from(value()?)),
+            "--frames" | "-f" => frames = parse_count("frames", &value()?)?,
+            "--scale" | "-s" => scale = parse_count("pixels per cell", &value()?)?,
+            "--delay" | "-d" => delay = parse_number("delay", &value()?, "milliseconds")?,
+            "--input" | "-i" => input = value()?.as_str().into(),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ if day.is_some() => return Err("`export` takes a single day".to_owned()),
+            _ => day = Some(parse_day(&arg)?),
+        }
+    }
+    Ok(Command::Export(ExportOptions {
+        day: day.ok_or("`export` needs a day")?,
+        input,
+        output: output.ok_or("`export` needs --output")?,
+        frames,
+        scale,
+        delay,
+    }))
+}
+
+fn parse_disassemble_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut message = None;
+    let mut input = InputSource::Resources;
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = split_flag(&arg);
+        let mut value = || flag_value(flag, inline_value, &mut args);
+        match flag {
+            "--input" | "-i" => input = value()?.as_str().into(),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ if message.is_some() => {
+                return Err("`disassemble` takes a single transmission".to_owned())
+            }
+            _ => message = Some(arg.clone()),
+        }
+    }
+    Ok(Command::Disassemble(DisassembleOptions { message, input }))
+}
+
+/// Splits `--flag=value` into the flag and its value. Short flags and other arguments are
+/// returned whole.
+fn split_flag(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((flag, value)) if arg.starts_with("--") => (flag, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+/// The value given with `--flag=value`, or else the next argument
+fn flag_value(
+    flag: &str,
+    inline_value: Option<&str>,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<String, String> {
+    inline_value
+        .map(|value| value.to_owned())
+        .or_else(|| args.next())
+        .ok_or(format!("{} needs a value", flag))
+}
+
+/// Parses the value of an option like `--seed`, `expected` says what it should be
+fn parse_number<T: FromStr>(name: &str, value: &str, expected: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid {} '{}', expected {}", name, value, expected))
+}
+
+/// Without any days, or with `--all`, every day is selected
+fn select_days(mut days: Vec<u8>, all: bool) -> Vec<u8> {
+    if all || days.is_empty() {
+        days = (1..=25).collect();
+    }
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// Parses the value of an option like `--jobs` that needs to be at least 1
+fn parse_count(name: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!(
+            "Invalid number of {} '{}', expected at least 1",
+            name, value
+        )),
+    }
+}
+
+fn parse_days(s: &str) -> Result<Vec<u8>, String> {
+    let mut days = Vec::new();
+    for segment in s.split(',').filter(|segment| !segment.is_empty()) {
+        if let Some((from, to)) = segment.split_once("..") {
+            let from = parse_day(from)?;
+            let to = match to.strip_prefix('=') {
+                Some(to) => parse_day(to)?,
+                None => parse_day(to)? - 1,
+            };
+            if from > to {
+                return Err(format!("Empty range of days '{}'", segment));
+            }
+            days.extend(from..=to);
+        } else {
+            days.push(parse_day(segment)?);
+        }
+    }
+    Ok(days)
+}
+
+fn parse_day(s: &str) -> Result<u8, String> {
+    match s.trim().parse() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "Invalid day '{}', expected a number from 1 to 25",
+            s
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        parse_args(args.split_ascii_whitespace().map(|arg| arg.to_owned()))
+    }
+
+    #[test]
+    fn it_runs_everything_without_arguments() {
+        assert_eq!(
+            Command::Run(RunOptions {
+                days: (1..=25).collect(),
+                part: Part::Both,
+                variant: None,
+                input: InputSource::Resources,
+                jobs: 1,
+            }),
+            parse("").unwrap()
+        );
+        assert_eq!(parse("").unwrap(), parse("run --all").unwrap());
+    }
+
+    #[test]
+    fn it_parses_single_day_and_part() {
+        assert_eq!(
+            Command::Run(RunOptions {
+                days: vec![15],
+                part: Part::Two,
+                variant: None,
+                input: InputSource::Resources,
+                jobs: 1,
+            }),
+            parse("run 15 --part 2").unwrap()
+        );
+        assert_eq!(
+            parse("run 15 --part 2").unwrap(),
+            parse("15 --part=2").unwrap()
+        );
+    }
+
+    #[test]
+    fn it_parses_ranges_and_lists() {
+        let days = |args| match parse(args).unwrap() {
+            Command::Run(options) => options.days,
+            _ => panic!("expected a run command"),
+        };
+        assert_eq!((1..=10).collect::<Vec<u8>>(), days("run 1..=10"));
+        assert_eq!((1..10).collect::<Vec<u8>>(), days("run 1..10"));
+        assert_eq!(vec![3, 5, 7], days("run 7,3,5"));
+        assert_eq!(vec![1, 2, 3, 20], days("run 1..=3 20 2"));
+    }
+
+    #[test]
+    fn it_parses_variant() {
+        match parse("run 6 --variant naive").unwrap() {
+            Command::Run(options) => assert_eq!(Some("naive".to_owned()), options.variant),
+            _ => panic!("expected a run command"),
+        }
+    }
+
+    #[test]
+    fn it_parses_jobs() {
+        let jobs = |args| match parse(args).unwrap() {
+            Command::Run(options) => options.jobs,
+            _ => panic!("expected a run command"),
+        };
+        assert_eq!(1, jobs("run"));
+        assert_eq!(8, jobs("run --jobs 8"));
+        assert_eq!(2, jobs("-j 2 19 23"));
+        assert!(parse("run --jobs 0").is_err());
+        assert!(parse("run --jobs=many").is_err());
+    }
+
+    #[test]
+    fn it_parses_input() {
+        let input = |args| match parse(args).unwrap() {
+            Command::Run(options) => options.input,
+            _ => panic!("expected a run command"),
+        };
+        assert_eq!(InputSource::Resources, input("run 17"));
+        assert_eq!(InputSource::Stdin, input("run 17 --input -"));
+        assert_eq!(InputSource::from("my.txt"), input("run 17 -i my.txt"));
+        assert_eq!(InputSource::from("my.txt"), input("17 --input=my.txt"));
+        assert!(parse("run 1..=3 --input my.txt").is_err());
+        assert!(parse("run --input my.txt").is_err());
+    }
+
+    #[test]
+    fn it_rejects_invalid_arguments() {
+        assert!(parse("run 26").is_err());
+        assert!(parse("run 0").is_err());
+        assert!(parse("run 5..=3").is_err());
+        assert!(parse("run 1 --part 3").is_err());
+        assert!(parse("run 1 --part").is_err());
+        assert!(parse("run --verbose").is_err());
+    }
+
+    #[test]
+    fn it_parses_verify() {
+        assert_eq!(
+            Command::Verify(VerifyOptions {
+                days: (1..=25).collect(),
+                variant: None,
+                answers: PathBuf::from(ANSWERS_PATH),
+                jobs: 1,
+            }),
+            parse("verify").unwrap()
+        );
+        assert_eq!(
+            Command::Verify(VerifyOptions {
+                days: vec![6, 23],
+                variant: Some("naive".to_owned()),
+                answers: PathBuf::from("my_answers.toml"),
+                jobs: 4,
+            }),
+            parse("verify 6,23 --variant naive --answers=my_answers.toml -j 4").unwrap()
+        );
+        assert!(parse("verify 1 --part 1").is_err());
+    }
+
+    #[test]
+    fn it_parses_bench() {
+        assert_eq!(
+            Command::Bench(BenchOptions {
+                days: vec![23],
+                variant: None,
+                iterations: 5,
+                format: Format::Text,
+            }),
+            parse("bench 23").unwrap()
+        );
+        assert_eq!(
+            Command::Bench(BenchOptions {
+                days: (1..=25).collect(),
+                variant: Some("fast".to_owned()),
+                iterations: 20,
+                format: Format::Json,
+            }),
+            parse("bench -v fast -n 20 --format=json").unwrap()
+        );
+        assert!(parse("bench -n 0").is_err());
+        assert!(parse("bench --format xml").is_err());
+    }
+
+    #[test]
+    fn it_parses_generate() {
+        assert_eq!(
+            Command::Generate(GenerateOptions {
+                rooms: 4,
+                depth: 2,
+                seed: 0,
+                count: 1,
+            }),
+            parse("generate").unwrap()
+        );
+        assert_eq!(
+            Command::Generate(GenerateOptions {
+                rooms: 5,
+                depth: 4,
+                seed: 42,
+                count: 10,
+            }),
+            parse("generate --rooms 5 -d 4 --seed=42 -n 10").unwrap()
+        );
+        assert!(parse("generate --rooms 256").is_err());
+        assert!(parse("generate --depth 0").is_err());
+        assert!(parse("generate 23").is_err());
+    }
+
+    #[test]
+    fn it_parses_play() {
+        assert_eq!(
+            Command::Play(PlayOptions {
+                day: 25,
+                input: InputSource::Resources,
+                delay: 100,
+                paused: false,
+                frames: None,
+            }),
+            parse("play 25").unwrap()
+        );
+        assert_eq!(
+            Command::Play(PlayOptions {
+                day: 11,
+                input: InputSource::Stdin,
+                delay: 0,
+                paused: true,
+                frames: Some(50),
+            }),
+            parse("play --delay=0 11 --paused -f 50 -i -").unwrap()
+        );
+        assert!(parse("play").is_err());
+        assert!(parse("play 5 11").is_err());
+        assert!(parse("play 5 --frames 0").is_err());
+    }
+
+    #[test]
+    fn it_parses_export() {
+        assert_eq!(
+            Command::Export(ExportOptions {
+                day: 11,
+                input: InputSource::Resources,
+                output: PathBuf::from("flashes.gif"),
+                frames: 100,
+                scale: 4,
+                delay: 100,
+            }),
+            parse("export 11 -o flashes.gif").unwrap()
+        );
+        assert_eq!(
+            Command::Export(ExportOptions {
+                day: 25,
+                input: InputSource::Path(PathBuf::from("example.txt")),
+                output: PathBuf::from("herd.png"),
+                frames: 10,
+                scale: 1,
+                delay: 50,
+            }),
+            parse("export --output=herd.png 25 -f 10 --scale 1 -d 50 -i example.txt").unwrap()
+        );
+        assert!(parse("export 11").is_err());
+        assert!(parse("export -o flashes.gif").is_err());
+        assert!(parse("export 11 -o flashes.gif --scale 0").is_err());
+    }
+
+    #[test]
+    fn it_parses_disassemble() {
+        assert_eq!(
+            Command::Disassemble(DisassembleOptions {
+                message: None,
+                input: InputSource::Resources,
+            }),
+            parse("disassemble").unwrap()
+        );
+        assert_eq!(
+            Command::Disassemble(DisassembleOptions {
+                message: Some("D2FE28".to_owned()),
+                input: InputSource::Resources,
+            }),
+            parse("disassemble D2FE28").unwrap()
+        );
+        assert_eq!(
+            Command::Disassemble(DisassembleOptions {
+                message: None,
+                input: InputSource::Stdin,
+            }),
+            parse("disassemble --input=-").unwrap()
+        );
+        assert!(parse("disassemble D2FE28 38006F45291200").is_err());
+        assert!(parse("disassemble --verbose").is_err());
+    }
+
+    #[test]
+    fn it_reads_flag_values() {
+        assert_eq!(("--seed", Some("4=2")), split_flag("--seed=4=2"));
+        assert_eq!(("-s=4", None), split_flag("-s=4"));
+        assert_eq!(
+            Err("Invalid seed 'x', expected a number".to_owned()),
+            parse("generate --seed=x")
+        );
+        assert_eq!(Err("-n needs a value".to_owned()), parse("bench -n"));
+        assert_eq!(
+            Err("Invalid delay '-1', expected milliseconds".to_owned()),
+            parse("play 5 -d -1")
+        );
+    }
+
+    #[test]
+    fn it_parses_other_commands() {
+        assert_eq!(Command::List, parse("list").unwrap());
+        assert_eq!(Command::Help, parse("--help").unwrap());
+    }
+}
@@ -1,14 +1,27 @@
-pub fn solve() {
-    let input: Vec<usize> = std::fs::read_to_string("./resources/day01.txt")
-        .unwrap()
-        .lines()
-        .map(|l| l.parse().unwrap())
-        .collect();
-    println!("Day 1 part 1: {}", get_number_of_increases(&input));
-    println!(
-        "Day 1 part 2: {}",
-        get_number_of_increases_windowed(&input, 3)
-    );
+use crate::{
+    error::Error,
+    parse::parse_lines,
+    solution::Solution,
+};
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, |line| Ok(line.trim().parse()?))
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(get_number_of_increases(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(get_number_of_increases_windowed(input, 3))
+    }
 }
 
 pub fn get_number_of_increases(depths: &[usize]) -> usize {
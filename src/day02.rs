@@ -1,37 +1,60 @@
-pub fn solve() {
-    let input = std::fs::read_to_string("./resources/day02.txt").unwrap();
-    let instructions = parse_input(&input);
-    let position = instructions.as_slice().follow();
-    println!("Day 2 part 1: {}", position.0 * position.1);
-    let instructions: Vec<AimInstruction> = instructions.into_iter().map(|i| i.into()).collect();
-    let position = instructions.as_slice().follow();
-    println!("Day 2 part 2: {}", position.0 * position.1);
+use std::str::FromStr;
+
+use crate::{
+    error::Error,
+    parse::parse_lines,
+    solution::Solution,
+};
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<Instruction>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let position = input.as_slice().follow();
+        Ok(position.0 * position.1)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        let instructions: Vec<AimInstruction> = input.iter().cloned().map(|i| i.into()).collect();
+        let position = instructions.as_slice().follow();
+        Ok(position.0 * position.1)
+    }
 }
 
-#[derive(PartialEq, Debug)]
-enum Direction {
+#[derive(PartialEq, Debug, Clone)]
+pub enum Direction {
     Down,
     Forward,
     Up,
 }
 
-impl From<&str> for Direction {
-    fn from(value: &str) -> Direction {
+impl FromStr for Direction {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
-            "down" => Direction::Down,
-            "forward" => Direction::Forward,
-            "up" => Direction::Up,
-            _ => panic!(),
+            "down" => Ok(Direction::Down),
+            "forward" => Ok(Direction::Forward),
+            "up" => Ok(Direction::Up),
+            _ => Err(Error::invalid(format!("unknown direction '{}'", value))),
         }
     }
 }
 
-fn parse_input(input: &str) -> Vec<Instruction> {
-    input.trim().lines().map(|line| line.into()).collect()
+fn parse_input(input: &str) -> Result<Vec<Instruction>, Error> {
+    parse_lines(input, |line| line.parse())
 }
 
-#[derive(PartialEq, Debug)]
-struct Instruction(Direction, usize);
+#[derive(PartialEq, Debug, Clone)]
+pub struct Instruction(Direction, usize);
 
 #[derive(PartialEq, Debug)]
 struct AimInstruction(Direction, usize);
@@ -77,13 +100,15 @@ impl Follow for &[Instruction] {
     }
 }
 
-impl From<&str> for Instruction {
-    fn from(value: &str) -> Self {
-        if let Some((direction, distance)) = value.trim().split_once(" ") {
-            Instruction(direction.into(), distance.parse().unwrap())
-        } else {
-            panic!()
-        }
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (direction, distance) = value
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| Error::invalid("cannot split into direction and distance"))?;
+        Ok(Instruction(direction.parse()?, distance.parse()?))
     }
 }
 
@@ -92,13 +117,16 @@ mod tests {
     use super::*;
     #[test]
     fn direction_parsing() {
-        assert_eq!(Direction::Down, "down".try_into().unwrap());
+        assert_eq!(Ok(Direction::Down), "down".parse());
+        assert!("sideways".parse::<Direction>().is_err());
     }
 
     #[test]
     fn instruction_parsing() {
-        let instruction: Instruction = "forward 5".try_into().unwrap();
+        let instruction: Instruction = "forward 5".parse().unwrap();
         assert_eq!(Instruction(Direction::Forward, 5), instruction);
+        assert!("forward".parse::<Instruction>().is_err());
+        assert!("forward x".parse::<Instruction>().is_err());
     }
 
     #[test]
@@ -109,8 +137,9 @@ mod tests {
                 Instruction(Direction::Forward, 5),
                 Instruction(Direction::Down, 7)
             ],
-            parse_input(input)
+            parse_input(input).unwrap()
         );
+        assert_eq!(Some(2), parse_input("forward 5\nup").unwrap_err().line);
     }
 
     #[test]
@@ -121,7 +150,7 @@ mod tests {
         up 3
         down 8
         forward 2";
-        assert_eq!((15, 10), parse_input(input).as_slice().follow());
+        assert_eq!((15, 10), parse_input(input).unwrap().as_slice().follow());
     }
 
     #[test]
@@ -133,7 +162,7 @@ mod tests {
         down 8
         forward 2";
         let instructions: Vec<AimInstruction> =
-            parse_input(input).into_iter().map(|i| i.into()).collect();
+            parse_input(input).unwrap().into_iter().map(|i| i.into()).collect();
         assert_eq!((15, 60), instructions.as_slice().follow());
     }
 }
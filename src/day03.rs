@@ -1,14 +1,29 @@
-pub fn solve() {
-    let input = std::fs::read_to_string("resources/day03.txt").unwrap();
-    let input: Input = input.as_str().into();
-    println!(
-        "Day 3 part 1: {}",
-        input.get_epsilon_rate() * input.get_gamma_rate()
-    );
-    println!(
-        "Day 3 part 2: {}",
-        input.get_co2_rating() * input.get_oxygen_rating()
-    );
+use std::str::FromStr;
+
+use crate::{
+    error::Error,
+    parse::parse_lines,
+    solution::Solution,
+};
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Input;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.get_epsilon_rate() * input.get_gamma_rate())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.get_co2_rating()? * input.get_oxygen_rating()?)
+    }
 }
 
 #[derive(Clone)]
@@ -38,50 +53,50 @@ impl Input {
             >= self.values.len()
     }
 
-    pub fn get_oxygen_rating(&self) -> usize {
+    pub fn get_oxygen_rating(&self) -> Result<usize, Error> {
         let cloned_uboat = self.clone();
         let cloned_uboat = cloned_uboat.filter_oxygen_rating();
-        cloned_uboat.values[0]
+        cloned_uboat
+            .values
+            .first()
+            .copied()
+            .ok_or_else(|| Error::unsolvable("no value is left for the oxygen rating"))
     }
 
     fn filter_oxygen_rating(mut self) -> Self {
         for i in (0..self.bit_length).rev() {
             let most_common_bit = self.get_most_common_bit(i);
-            self.values = self
-                .values
-                .into_iter()
-                .filter(|v| {
+            self.values.retain(|v| {
                     if most_common_bit {
                         v & 1 << i != 0
                     } else {
                         v & 1 << i == 0
                     }
-                })
-                .collect();
+                });
         }
         self
     }
 
-    pub fn get_co2_rating(&self) -> usize {
+    pub fn get_co2_rating(&self) -> Result<usize, Error> {
         let cloned_uboat = self.clone();
         let cloned_uboat = cloned_uboat.filter_co2_rating();
-        cloned_uboat.values[0]
+        cloned_uboat
+            .values
+            .first()
+            .copied()
+            .ok_or_else(|| Error::unsolvable("no value is left for the CO2 rating"))
     }
 
     fn filter_co2_rating(mut self) -> Self {
         for i in (0..self.bit_length).rev() {
             let least_common_bit = !self.get_most_common_bit(i);
-            self.values = self
-                .values
-                .into_iter()
-                .filter(|v| {
+            self.values.retain(|v| {
                     if least_common_bit {
                         v & 1 << i != 0
                     } else {
                         v & 1 << i == 0
                     }
-                })
-                .collect();
+                });
             if self.values.len() == 1 {
                 return self;
             }
@@ -100,16 +115,27 @@ impl Input {
     }
 }
 
-impl From<&str> for Input {
-    fn from(input: &str) -> Self {
-        Input {
-            values: input
-                .trim()
-                .lines()
-                .map(|line| usize::from_str_radix(line.trim(), 2).unwrap())
-                .collect(),
-            bit_length: input.lines().next().unwrap().len(),
-        }
+impl FromStr for Input {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let bit_length = input
+            .lines()
+            .next()
+            .ok_or_else(|| Error::invalid("empty input"))?
+            .trim()
+            .len();
+        let values = parse_lines(input, |line| {
+            if line.trim().len() != bit_length {
+                return Err(Error::invalid(format!(
+                    "expected {} bits, got '{}'",
+                    bit_length,
+                    line.trim()
+                )));
+            }
+            Ok(usize::from_str_radix(line.trim(), 2)?)
+        })?;
+        Ok(Input { values, bit_length })
     }
 }
 
@@ -130,11 +156,18 @@ mod tests {
         11001
         00010
         01010";
-        let input: Input = input.into();
+        let input: Input = input.parse().unwrap();
         assert_eq!(5, input.bit_length);
         assert_eq!(4, input.values[0])
     }
 
+    #[test]
+    fn it_rejects_invalid_input() {
+        assert_eq!(Some(2), "00100\n0110".parse::<Input>().err().and_then(|e| e.line));
+        assert_eq!(Some(2), "00100\n01120".parse::<Input>().err().and_then(|e| e.line));
+        assert!("".parse::<Input>().is_err());
+    }
+
     #[test]
     fn it_calculates_gamma() {
         let input = r"00100
@@ -149,7 +182,7 @@ mod tests {
         11001
         00010
         01010";
-        let input: Input = input.into();
+        let input: Input = input.parse().unwrap();
         assert_eq!(22, input.get_gamma_rate());
     }
 
@@ -167,7 +200,7 @@ mod tests {
         11001
         00010
         01010";
-        let input: Input = input.into();
+        let input: Input = input.parse().unwrap();
         assert_eq!(9, input.get_epsilon_rate());
     }
 
@@ -179,8 +212,8 @@ mod tests {
         10000
         11001
         00010";
-        let input: Input = input.into();
-        assert_eq!(true, input.get_most_common_bit(0));
+        let input: Input = input.parse().unwrap();
+        assert!(input.get_most_common_bit(0));
     }
 
     #[test]
@@ -197,8 +230,8 @@ mod tests {
         11001
         00010
         01010";
-        let input: Input = input.into();
-        assert_eq!(23, input.get_oxygen_rating())
+        let input: Input = input.parse().unwrap();
+        assert_eq!(Ok(23), input.get_oxygen_rating())
     }
 
     #[test]
@@ -215,7 +248,7 @@ mod tests {
         11001
         00010
         01010";
-        let input: Input = input.into();
-        assert_eq!(10, input.get_co2_rating())
+        let input: Input = input.parse().unwrap();
+        assert_eq!(Ok(10), input.get_co2_rating())
     }
 }
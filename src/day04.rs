@@ -1,28 +1,36 @@
-use std::{num::ParseIntError, str::FromStr};
-
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut game: BingoGame = input.parse().unwrap();
-    println!("Day 4 part 1: {}", game.get_winning_score());
-    let mut game: BingoGame = input.parse().unwrap();
-    println!("Day 4 part 2: {}", game.get_losing_score());
+use std::str::FromStr;
+
+use crate::{error::Error, solution::Solution};
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = BingoGame;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.clone().get_winning_score())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.clone().get_losing_score())
+    }
 }
 
 const BINGO_SIZE: usize = 5;
 
-#[derive(Debug)]
-pub struct BingoError(String);
-
 #[derive(Debug, PartialEq)]
 pub struct BingoResult {
     after_number: usize,
     score: usize,
 }
 
+#[derive(Clone)]
 pub struct BingoBoard {
     grid: [Option<u8>; BINGO_SIZE * BINGO_SIZE],
 }
@@ -78,24 +86,28 @@ impl BingoBoard {
 }
 
 impl FromStr for BingoBoard {
-    type Err = BingoError;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let grid = s
             .trim()
             .split_ascii_whitespace()
-            .map(|v| {
-                v.trim().parse().map(Some).map_err(|e: ParseIntError| {
-                    BingoError(format!("Cannot parse number {}", e.to_string()))
-                })
-            })
-            .collect::<Result<Vec<Option<u8>>, BingoError>>()?;
+            .map(|v| Ok(Some(v.trim().parse()?)))
+            .collect::<Result<Vec<Option<u8>>, Error>>()?;
+        let count = grid.len();
         Ok(BingoBoard {
-            grid: grid.try_into().unwrap(),
+            grid: grid.try_into().map_err(|_| {
+                Error::invalid(format!(
+                    "expected {} numbers on a board, got {}",
+                    BINGO_SIZE * BINGO_SIZE,
+                    count
+                ))
+            })?,
         })
     }
 }
 
+#[derive(Clone)]
 pub struct BingoGame {
     boards: Vec<BingoBoard>,
     sequence: Vec<u8>,
@@ -130,29 +142,26 @@ impl BingoGame {
 }
 
 impl FromStr for BingoGame {
-    type Err = BingoError;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
         let sequence: Vec<u8> = s
             .lines()
             .next()
-            .ok_or_else(|| BingoError("Empty input".to_owned()))?
+            .ok_or_else(|| Error::invalid("empty input"))?
             .split(',')
-            .map(|v| {
-                v.parse().map_err(|e: ParseIntError| {
-                    BingoError(format!("Cannot parse number {}", e.to_string()))
-                })
-            })
-            .collect::<Result<Vec<u8>, BingoError>>()?;
-        let boards: Vec<BingoBoard> = s
-            .split("\n\n")
-            .skip(1)
-            .map(|s| {
-                s.parse()
-                    .map_err(|_| BingoError("Cannot parse bingo board".to_owned()))
-            })
-            .collect::<Result<Vec<BingoBoard>, BingoError>>()?;
+            .map(|v| Ok(v.parse()?))
+            .collect::<Result<Vec<u8>, Error>>()
+            .map_err(|e| e.at_line(1))?;
+        let mut boards: Vec<BingoBoard> = Vec::new();
+        let mut line = 1;
+        for (i, board) in s.split("\n\n").enumerate() {
+            if i > 0 {
+                boards.push(board.parse().map_err(|e: Error| e.at_line(line))?);
+            }
+            line += board.lines().count() + 1;
+        }
         Ok(BingoGame { boards, sequence })
     }
 }
@@ -169,7 +178,7 @@ mod tests {
         board.grid[2] = Some(2);
         board.play_card(2);
         assert_eq!(board.grid[2], None);
-        assert_eq!(board.grid[1].is_some(), true);
+        assert!(board.grid[1].is_some());
     }
 
     #[test]
@@ -228,13 +237,22 @@ mod tests {
 18  8 23 26 20
 22 11 13  6  5
  2  0 12  3  7";
-        let game: Result<BingoGame, BingoError> = input.parse();
-        assert_eq!(true, game.is_ok());
+        let game: Result<BingoGame, Error> = input.parse();
+        assert!(game.is_ok());
         let game = game.unwrap();
         assert_eq!(vec![7, 4, 9, 5], game.sequence[0..4]);
         assert_eq!(3, game.boards.len());
     }
 
+    #[test]
+    fn it_rejects_invalid_boards() {
+        let error = "1,2\n\n1 2 x".parse::<BingoGame>().err().unwrap();
+        assert_eq!(Some(3), error.line);
+        let error = "1,2\n\n1 2 3 4 5".parse::<BingoGame>().err().unwrap();
+        assert!(error.to_string().contains("25 numbers"));
+        assert_eq!(Some(1), "1,a".parse::<BingoGame>().err().unwrap().line);
+    }
+
     #[test]
     fn it_finds_winner() {
         let input = r"7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
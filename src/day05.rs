@@ -4,135 +4,111 @@ use std::{
     str::FromStr,
 };
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let ocean: Ocean = input.parse().unwrap();
-    println!(
-        "Day 5 part 1: {}",
-        ocean.get_overlapping_points(false).len()
-    );
-    println!("Day 5 part 2: {}", ocean.get_overlapping_points(true).len());
+use crate::{
+    error::Error,
+    geometry::{Rectangle, Vector2},
+    parse::parse_lines,
+    playback::Simulation,
+    solution::Solution,
+};
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Ocean;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.get_overlapping_points(false).len())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.get_overlapping_points(true).len())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Vent {
-    start: Point,
-    end: Point,
+    start: Vector2,
+    end: Vector2,
 }
 
 impl Vent {
-    fn is_vertical(&self) -> bool {
-        self.start.x == self.end.x
+    pub fn is_vertical(&self) -> bool {
+        self.start.x() == self.end.x()
     }
 
-    fn is_horizontal(&self) -> bool {
-        self.start.y == self.end.y
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y() == self.end.y()
     }
 
-    fn get_covering_points(&self) -> HashSet<Point> {
-        let mut covering_points = HashSet::new();
-        if !self.is_horizontal() && !self.is_vertical() {
-            match (self.end.x.cmp(&self.start.x), self.end.y.cmp(&self.start.y)) {
-                (std::cmp::Ordering::Less, std::cmp::Ordering::Less) => {
-                    for (i, x) in (self.end.x..=self.start.x).enumerate() {
-                        covering_points.insert((x, self.end.y + i).into());
-                    }
-                }
-                (std::cmp::Ordering::Less, std::cmp::Ordering::Greater) => {
-                    for (i, x) in (self.end.x..=self.start.x).enumerate() {
-                        covering_points.insert((x, self.end.y - i).into());
-                    }
-                }
-                (std::cmp::Ordering::Greater, std::cmp::Ordering::Less) => {
-                    for (i, x) in (self.start.x..=self.end.x).enumerate() {
-                        covering_points.insert((x, self.start.y - i).into());
-                    }
-                }
-                (std::cmp::Ordering::Greater, std::cmp::Ordering::Greater) => {
-                    for (i, x) in (self.start.x..=self.end.x).enumerate() {
-                        covering_points.insert((x, self.start.y + i).into());
-                    }
-                }
-                _ => panic!("Somehow, a vertical or horizontal vent is in the diagonals"),
-            }
-        } else {
-            for x in self.start.x.min(self.end.x)..=self.start.x.max(self.end.x) {
-                for y in self.start.y.min(self.end.y)..=self.start.y.max(self.end.y) {
-                    covering_points.insert((x, y).into());
-                }
-            }
+    /// Vents are horizontal, vertical or diagonal, so one step at a time reaches every point
+    pub fn get_covering_points(&self) -> HashSet<Vector2> {
+        let step = (self.end - self.start).signum();
+        let mut covering_points = HashSet::from([self.start]);
+        let mut point = self.start;
+        while point != self.end {
+            point += step;
+            covering_points.insert(point);
         }
-
         covering_points
     }
 }
 
 impl FromStr for Vent {
-    type Err = VentParseError;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (start, end) = s.split_once(" -> ").ok_or(VentParseError::Vent)?;
-        Ok(Vent {
+        let (start, end) = s
+            .trim()
+            .split_once(" -> ")
+            .ok_or_else(|| Error::invalid("cannot split vent into start and end"))?;
+        let vent = Vent {
             start: start.parse()?,
             end: end.parse()?,
-        })
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl From<(usize, usize)> for Point {
-    fn from((x, y): (usize, usize)) -> Self {
-        Point { x, y }
-    }
-}
-
-impl FromStr for Point {
-    type Err = VentParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').ok_or(VentParseError::Point)?;
-        let x = x.parse().map_err(|_| VentParseError::Int)?;
-        let y = y.parse().map_err(|_| VentParseError::Point)?;
-        Ok((x, y).into())
+        };
+        if !vent.is_horizontal()
+            && !vent.is_vertical()
+            && vent.start.x().abs_diff(vent.end.x()) != vent.start.y().abs_diff(vent.end.y())
+        {
+            return Err(Error::invalid(format!(
+                "vent '{}' is neither horizontal, vertical nor diagonal",
+                s.trim()
+            )));
+        }
+        Ok(vent)
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum VentParseError {
-    Point,
-    Int,
-    Vent,
-}
-
 pub struct Ocean {
     vents: Vec<Vent>,
+    /// How many of the vents are shown, the others only count for the size of the map
+    drawn: usize,
 }
 
 impl FromStr for Ocean {
-    type Err = VentParseError;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let vents: Vec<Vent> = s
-            .trim()
-            .lines()
-            .map(|l| l.trim().parse())
-            .collect::<Result<Vec<Vent>, VentParseError>>()?;
-        Ok(Ocean { vents })
+        let vents: Vec<Vent> = parse_lines(s, |line| line.parse())?;
+        let drawn = vents.len();
+        Ok(Ocean { vents, drawn })
     }
 }
 
 impl Ocean {
-    fn get_overlapping_points(&self, with_diagonals: bool) -> HashSet<Point> {
-        let mut overlaps: HashMap<Point, usize> = HashMap::new();
+    /// The same ocean with no vents drawn yet, every step of the simulation adds one
+    pub fn without_vents(self) -> Ocean {
+        Ocean { drawn: 0, ..self }
+    }
+
+    pub fn get_overlapping_points(&self, with_diagonals: bool) -> HashSet<Vector2> {
+        let mut overlaps: HashMap<Vector2, usize> = HashMap::new();
         for vent in self
             .vents
             .iter()
@@ -155,38 +131,18 @@ impl Ocean {
 
 impl Display for Ocean {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let min_x = self
-            .vents
-            .iter()
-            .map(|v| v.start.x.min(v.end.x))
-            .min()
-            .ok_or(std::fmt::Error)?;
-        let min_y = self
-            .vents
-            .iter()
-            .map(|v| v.start.y.min(v.end.y))
-            .min()
-            .ok_or(std::fmt::Error)?;
-        let max_x = self
-            .vents
-            .iter()
-            .map(|v| v.start.x.max(v.end.x))
-            .max()
-            .ok_or(std::fmt::Error)?;
-        let max_y = self
-            .vents
-            .iter()
-            .map(|v| v.start.y.max(v.end.y))
-            .max()
-            .ok_or(std::fmt::Error)?;
-        let width = max_x - min_x + 1;
-        let height = max_y - min_y + 1;
-        let mut grid = vec![0usize; width * height];
-        for vent in self.vents.iter()
-        //.filter(|v| v.is_horizontal() || v.is_vertical())
-        {
+        let corners = self.vents.iter().flat_map(|v| [v.start, v.end]);
+        // Without vents there is no map, so the frame stays empty
+        let bounds = match Rectangle::enclosing(corners) {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        let width = (bounds.max.x() - bounds.min.x() + 1) as usize;
+        let mut grid = vec![0usize; bounds.volume()];
+        for vent in self.vents.iter().take(self.drawn) {
             for point in vent.get_covering_points() {
-                grid[(point.y - min_y) * width + (point.x - min_x)] += 1;
+                let offset = point - bounds.min;
+                grid[offset.y() as usize * width + offset.x() as usize] += 1;
             }
         }
         for (i, p) in grid.iter().enumerate() {
@@ -203,22 +159,33 @@ impl Display for Ocean {
     }
 }
 
+impl Simulation for Ocean {
+    fn step(&mut self) -> bool {
+        if self.drawn < self.vents.len() {
+            self.drawn += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn it_parses_vent() {
-        let vent: Result<Vent, VentParseError> = "1,1 -> 1,3".parse();
+        let vent: Result<Vent, Error> = "1,1 -> 1,3".parse();
         assert!(vent.is_ok());
         let vent = vent.unwrap();
-        assert_eq!(Point::from((1, 1)), vent.start);
-        assert_eq!(Point::from((1, 3)), vent.end);
+        assert_eq!(Vector2::new(1, 1), vent.start);
+        assert_eq!(Vector2::new(1, 3), vent.end);
     }
     #[test]
     fn it_calculates_covering_points() {
         let vent = Vent {
-            start: (1, 1).into(),
-            end: (1, 3).into(),
+            start: Vector2::new(1, 1),
+            end: Vector2::new(1, 3),
         };
         let covering_points = vent.get_covering_points();
         assert_eq!(3, covering_points.len());
@@ -228,12 +195,27 @@ mod tests {
     fn it_parses_ocean() {
         let ocean = r"0,9 -> 5,9
 0,9 -> 2,9";
-        let ocean: Result<Ocean, VentParseError> = ocean.parse();
+        let ocean: Result<Ocean, Error> = ocean.parse();
         assert!(ocean.is_ok());
         let ocean = ocean.unwrap();
         assert_eq!(2, ocean.vents.len());
     }
 
+    #[test]
+    fn it_draws_an_empty_ocean() {
+        let ocean: Ocean = "".parse().unwrap();
+        assert_eq!("", ocean.to_string());
+        assert_eq!(0, ocean.get_overlapping_points(true).len());
+    }
+
+    #[test]
+    fn it_rejects_invalid_vents() {
+        let error = "0,9 -> 5,9\n0,9 -> 2,x".parse::<Ocean>().err().unwrap();
+        assert_eq!(Some(2), error.line);
+        assert!("0,9 -> 2,8".parse::<Vent>().is_err());
+        assert!("0,9 - 2,9".parse::<Vent>().is_err());
+    }
+
     #[test]
     fn it_calculates_overlaps() {
         let ocean = r"0,9 -> 5,9
@@ -241,10 +223,10 @@ mod tests {
         let ocean: Ocean = ocean.parse().unwrap();
         let overlaps = ocean.get_overlapping_points(false);
         assert_eq!(3, overlaps.len());
-        assert!(overlaps.contains(&Point::from((0, 9))));
-        assert!(overlaps.contains(&Point::from((1, 9))));
-        assert!(overlaps.contains(&Point::from((2, 9))));
-        assert_eq!(false, overlaps.contains(&Point::from((3, 9))));
+        assert!(overlaps.contains(&Vector2::new(0, 9)));
+        assert!(overlaps.contains(&Vector2::new(1, 9)));
+        assert!(overlaps.contains(&Vector2::new(2, 9)));
+        assert!(!overlaps.contains(&Vector2::new(3, 9)));
     }
 
     #[test]
@@ -260,7 +242,6 @@ mod tests {
         0,0 -> 8,8
         5,5 -> 8,2";
         let ocean: Ocean = ocean.parse().unwrap();
-        //println!("{}", ocean);
         assert_eq!(5, ocean.get_overlapping_points(false).len());
     }
 
@@ -269,30 +250,30 @@ mod tests {
         let vent: Vent = "1,1 -> 3,3".parse().unwrap();
         let coverings = vent.get_covering_points();
         assert_eq!(3, coverings.len());
-        assert!(coverings.contains(&Point::from((1, 1))));
-        assert!(coverings.contains(&Point::from((2, 2))));
-        assert!(coverings.contains(&Point::from((3, 3))));
+        assert!(coverings.contains(&Vector2::new(1, 1)));
+        assert!(coverings.contains(&Vector2::new(2, 2)));
+        assert!(coverings.contains(&Vector2::new(3, 3)));
 
         let vent: Vent = "3,3 -> 1,1".parse().unwrap();
         let coverings = vent.get_covering_points();
         assert_eq!(3, coverings.len());
-        assert!(coverings.contains(&Point::from((1, 1))));
-        assert!(coverings.contains(&Point::from((2, 2))));
-        assert!(coverings.contains(&Point::from((3, 3))));
+        assert!(coverings.contains(&Vector2::new(1, 1)));
+        assert!(coverings.contains(&Vector2::new(2, 2)));
+        assert!(coverings.contains(&Vector2::new(3, 3)));
 
         let vent: Vent = "9,7 -> 7,9".parse().unwrap();
         let coverings = vent.get_covering_points();
         assert_eq!(3, coverings.len());
-        assert!(coverings.contains(&Point::from((9, 7))));
-        assert!(coverings.contains(&Point::from((8, 8))));
-        assert!(coverings.contains(&Point::from((7, 9))));
+        assert!(coverings.contains(&Vector2::new(9, 7)));
+        assert!(coverings.contains(&Vector2::new(8, 8)));
+        assert!(coverings.contains(&Vector2::new(7, 9)));
 
         let vent: Vent = "7,9 -> 9,7".parse().unwrap();
         let coverings = vent.get_covering_points();
         assert_eq!(3, coverings.len());
-        assert!(coverings.contains(&Point::from((9, 7))));
-        assert!(coverings.contains(&Point::from((8, 8))));
-        assert!(coverings.contains(&Point::from((7, 9))));
+        assert!(coverings.contains(&Vector2::new(9, 7)));
+        assert!(coverings.contains(&Vector2::new(8, 8)));
+        assert!(coverings.contains(&Vector2::new(7, 9)));
     }
 
     #[test]
@@ -308,7 +289,6 @@ mod tests {
         0,0 -> 8,8
         5,5 -> 8,2";
         let ocean: Ocean = ocean.parse().unwrap();
-        //println!("{}", ocean);
         assert_eq!(12, ocean.get_overlapping_points(true).len());
     }
 }
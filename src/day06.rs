@@ -1,15 +1,32 @@
 
 use std::str::FromStr;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut school: School = input.parse().unwrap();
-    school.nth(79);
-    println!("Day 6 part 1 (slow): {}", school.len());
+use crate::{error::Error, solution::Solution};
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = School;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const SOLVES_PART2: bool = false;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let mut school = input.clone();
+        school.nth(79);
+        Ok(school.len())
+    }
+
+    fn part2(_input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Err(Error::unsupported(
+            "the naive simulation does not scale to 256 days",
+        ))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -43,14 +60,21 @@ impl PartialEq<u8> for Lanternfish {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct School(Vec<Lanternfish>);
 
 impl FromStr for School {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(School(s.trim().split(',').map(|s| Lanternfish::with_timer(s.parse().unwrap())).collect()))
+        s.trim()
+            .split(',')
+            .map(|s| match s.trim().parse()? {
+                timer @ 0..=8 => Ok(Lanternfish::with_timer(timer)),
+                timer => Err(Error::invalid(format!("timer {} is out of range 0..=8", timer))),
+            })
+            .collect::<Result<Vec<Lanternfish>, Error>>()
+            .map(School)
     }
 }
 
@@ -71,9 +95,13 @@ impl Iterator for School {
 }
 
 impl School {
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 
@@ -91,6 +119,8 @@ mod tests {
         let school = school.unwrap();
         assert_eq!(5, school.0.len());
         assert_eq!(Lanternfish::with_timer(3), school.0[0]);
+        assert!("3,9".parse::<School>().is_err());
+        assert!("3,a".parse::<School>().is_err());
     }
 
     #[test]
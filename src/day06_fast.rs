@@ -1,15 +1,32 @@
 use std::str::FromStr;
 
-pub fn solve() {
-    let input = std::fs::read_to_string("resources/day06.txt").unwrap();
-    let mut school: School = input.parse().unwrap();
-    school.nth(79);
-    println!("Day 6 part 1: {}", school.len());
-    let mut school: School = input.parse().unwrap();
-    school.nth(255);
-    println!("Day 6 part 2: {}", school.len());
+use crate::{error::Error, solution::Solution};
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = School;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let mut school = input.clone();
+        school.nth(79);
+        Ok(school.len())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        let mut school = input.clone();
+        school.nth(255);
+        Ok(school.len())
+    }
 }
 
+#[derive(Clone)]
 pub struct School {
     fishes: [usize; 9],
 }
@@ -29,22 +46,28 @@ impl Iterator for School {
 }
 
 impl FromStr for School {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let fishes: Vec<usize> = s.trim().split(',').map(|s| s.parse().unwrap()).collect();
         let mut school = [0usize; 9];
-        for fish in fishes {
-            school[fish] += 1;
+        for fish in s.trim().split(',') {
+            let fish: usize = fish.trim().parse()?;
+            *school.get_mut(fish).ok_or_else(|| {
+                Error::invalid(format!("timer {} is out of range 0..=8", fish))
+            })? += 1;
         }
         Ok(School { fishes: school })
     }
 }
 
 impl School {
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.fishes.iter().sum()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 #[cfg(test)]
@@ -55,6 +78,7 @@ mod tests {
         let school: Result<School, _> = "3,4,3,1,2".parse();
         assert!(school.is_ok());
         assert_eq!([0, 1, 1, 2, 1, 0, 0, 0, 0], school.unwrap().fishes);
+        assert!("3,9".parse::<School>().is_err());
     }
 
     #[test]
@@ -1,28 +1,37 @@
 use std::str::FromStr;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let crabs = Crabs::from_str(&input).unwrap();
-    println!("Day 7 part 1: {}", crabs.get_cheapest_position());
-    println!(
-        "Day 7 part 2: {}",
-        crabs.get_cheapest_position_increasing_cost()
-    );
+use crate::{error::Error, solution::Solution};
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = Crabs;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.get_cheapest_position())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.get_cheapest_position_increasing_cost())
+    }
 }
+
 pub struct Crabs(Vec<usize>);
 
 impl Crabs {
-    fn get_cheapest_position(&self) -> usize {
+    pub fn get_cheapest_position(&self) -> usize {
         let mut best = usize::MAX;
-        for i in *self.0.iter().min().unwrap()..*self.0.iter().max().unwrap() {
+        for i in self.get_min()..=self.get_max() {
             let sum: usize = self
                 .0
                 .iter()
-                .map(|&v| (v as isize - i as isize).abs() as usize)
+                .map(|&v| (v as isize - i as isize).unsigned_abs())
                 .sum();
             if sum < best {
                 best = sum;
@@ -31,13 +40,13 @@ impl Crabs {
         best
     }
 
-    fn get_cheapest_position_increasing_cost(&self) -> usize {
+    pub fn get_cheapest_position_increasing_cost(&self) -> usize {
         let mut best = usize::MAX;
-        for i in *self.0.iter().min().unwrap()..*self.0.iter().max().unwrap() {
+        for i in self.get_min()..=self.get_max() {
             let sum: usize = self
                 .0
                 .iter()
-                .map(|&v| Crabs::get_cost((v as isize - i as isize).abs() as usize))
+                .map(|&v| Crabs::get_cost((v as isize - i as isize).unsigned_abs()))
                 .sum();
             if sum < best {
                 best = sum;
@@ -46,6 +55,14 @@ impl Crabs {
         best
     }
 
+    fn get_min(&self) -> usize {
+        self.0.iter().copied().min().unwrap_or(0)
+    }
+
+    fn get_max(&self) -> usize {
+        self.0.iter().copied().max().unwrap_or(0)
+    }
+
     fn get_cost(distance: usize) -> usize {
         //(1..=distance).sum()
         (distance * (distance + 1)) / 2 // gauss
@@ -54,14 +71,14 @@ impl Crabs {
 }
 
 impl FromStr for Crabs {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Crabs(
             s.trim()
                 .split(',')
-                .map(|crab| crab.parse().map_err(|_| "Cannot parse crab".to_owned()))
-                .collect::<Result<Vec<usize>, String>>()?,
+                .map(|crab| Ok(crab.trim().parse()?))
+                .collect::<Result<Vec<usize>, Error>>()?,
         ))
     }
 }
@@ -76,6 +93,7 @@ mod tests {
         assert!(crabs.is_ok());
         let crabs = crabs.unwrap();
         assert_eq!(10, crabs.0.len());
+        assert!(Crabs::from_str("1,,2").is_err());
     }
 
     #[test]
@@ -2,34 +2,40 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut entries: Vec<Entry> = input
-        .trim()
-        .split('\n')
-        .map(|e| e.trim().parse().unwrap())
-        .collect();
-    println!(
-        "Day 8 part 1: {}",
-        entries
+use crate::{
+    error::Error,
+    parse::parse_lines,
+    solution::Solution,
+};
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Vec<Entry>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, |line| line.trim().parse())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input
             .iter()
             .map(|entry| entry.get_simple_digits_in_output())
-            .sum::<usize>()
-    );
-    println!(
-        "Day 8 part 2: {}",
-        entries
-            .iter_mut()
-            .map(|entry| entry.determine_digits())
-            .sum::<usize>()
-    );
+            .sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        input
+            .iter()
+            .cloned()
+            .map(|mut entry| entry.determine_digits())
+            .sum()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Entry {
     //input_digits: [SevenSegmentDisplay; 10],
     //output_digits: [SevenSegmentDisplay; 4],
@@ -49,7 +55,16 @@ impl Entry {
             .count()
     }
 
-    pub fn determine_digits(&mut self) -> usize {
+    fn get_known(
+        sorted: &[Option<SevenSegmentDisplay>],
+        digit: usize,
+    ) -> Result<&SevenSegmentDisplay, Error> {
+        sorted[digit]
+            .as_ref()
+            .ok_or_else(|| Error::unsolvable(format!("cannot find the segments of {}", digit)))
+    }
+
+    pub fn determine_digits(&mut self) -> Result<usize, Error> {
         let mut sorted: Vec<Option<SevenSegmentDisplay>> = vec![None; 10];
 
         for digit in self.digits.iter_mut() {
@@ -73,13 +88,12 @@ impl Entry {
                 _ => {}
             }
         }
+        let one = Entry::get_known(&sorted, 1)?.input.clone();
         let four_minus_one = String::from_iter(
-            sorted[4]
-                .as_ref()
-                .unwrap()
+            Entry::get_known(&sorted, 4)?
                 .input
                 .chars()
-                .filter(|c| !sorted[1].as_ref().unwrap().input.contains(*c)),
+                .filter(|c| !one.contains(*c)),
         );
         for digit in self.digits.iter_mut() {
             if digit.input.len() == 5 {
@@ -90,12 +104,7 @@ impl Entry {
                     digit.digit = Some(5);
                 } else {
                     // it's a 2 or a 3. if it contains all of 1, it's a 3
-                    if sorted[1]
-                        .as_ref()
-                        .unwrap()
-                        .input
-                        .chars()
-                        .all(|c| digit.input.contains(c))
+                    if one.chars().all(|c| digit.input.contains(c))
                     {
                         sorted[3] = Some(digit.clone());
                         digit.digit = Some(3);
@@ -107,8 +116,9 @@ impl Entry {
             }
         }
 
-        let mut five_plus_one = sorted[5].as_ref().unwrap().input.clone();
-        five_plus_one.push_str(&sorted[1].as_ref().unwrap().input);
+        let mut five_plus_one = Entry::get_known(&sorted, 5)?.input.clone();
+        five_plus_one.push_str(&one);
+        let seven = Entry::get_known(&sorted, 7)?.input.clone();
         let five_plus_one = String::from_iter(five_plus_one.chars().sorted_unstable().unique());
         for digit in self.digits.iter_mut() {
             if digit.input.len() == 6 {
@@ -118,12 +128,7 @@ impl Entry {
                     digit.digit = Some(9);
                 } else {
                     // it's a 0 or 6. if it contains all segments of 7, it's a 0
-                    if sorted[7]
-                        .as_ref()
-                        .unwrap()
-                        .input
-                        .chars()
-                        .all(|c| digit.input.contains(c))
+                    if seven.chars().all(|c| digit.input.contains(c))
                     {
                         sorted[0] = Some(digit.clone());
                         digit.digit = Some(0);
@@ -136,52 +141,53 @@ impl Entry {
         }
         for digit in self.digits.iter_mut() {
             if digit.digit.is_none() {
-                digit.digit = Some(
-                    sorted
-                        .iter()
-                        .find(|elem| elem.as_ref().unwrap().input == digit.input)
-                        .unwrap()
-                        .as_ref()
-                        .unwrap()
-                        .digit
-                        .unwrap(),
-                );
+                digit.digit = sorted
+                    .iter()
+                    .flatten()
+                    .find(|known| known.input == digit.input)
+                    .and_then(|known| known.digit);
             }
         }
         let mut result = 0;
         for (i, out_digit) in self.get_output_slice().iter().enumerate() {
-            result += 10usize.pow(3 - i as u32) * out_digit.digit.unwrap() as usize;
+            let value = out_digit.digit.ok_or_else(|| {
+                Error::unsolvable(format!("cannot determine the digit of {}", out_digit.input))
+            })?;
+            result += 10usize.pow(3 - i as u32) * value as usize;
         }
-        result
+        Ok(result)
     }
 }
 
 impl FromStr for Entry {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (input, output) = s.split_once(" | ").ok_or_else(|| "No pipe".to_owned())?;
+        let (input, output) = s
+            .split_once(" | ")
+            .ok_or_else(|| Error::invalid("cannot split entry at the pipe"))?;
         let mut input_digits = input
             .trim()
             .split_ascii_whitespace()
-            .map(|display| {
-                SevenSegmentDisplay::from_str(display)
-                    .map_err(|_| "Cannot parse display".to_owned())
-            })
-            .collect::<Result<Vec<SevenSegmentDisplay>, String>>()?;
+            .map(SevenSegmentDisplay::from_str)
+            .collect::<Result<Vec<SevenSegmentDisplay>, Error>>()?;
         let mut output_digits = output
             .trim()
             .split_ascii_whitespace()
-            .map(|display| {
-                SevenSegmentDisplay::from_str(display)
-                    .map_err(|_| "Cannot parse display".to_owned())
-            })
-            .collect::<Result<Vec<SevenSegmentDisplay>, String>>()?;
+            .map(SevenSegmentDisplay::from_str)
+            .collect::<Result<Vec<SevenSegmentDisplay>, Error>>()?;
+        if input_digits.len() != 10 || output_digits.len() != 4 {
+            return Err(Error::invalid(format!(
+                "expected 10 patterns and 4 output digits, got {} and {}",
+                input_digits.len(),
+                output_digits.len()
+            )));
+        }
         input_digits.append(&mut output_digits);
         Ok(Entry {
             digits: input_digits
                 .try_into()
-                .map_err(|_| "cannot try into".to_owned())?,
+                .map_err(|_| Error::invalid("expected 14 digits"))?,
         })
     }
 }
@@ -193,9 +199,12 @@ pub struct SevenSegmentDisplay {
 }
 
 impl FromStr for SevenSegmentDisplay {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(c) = s.chars().find(|c| !('a'..='g').contains(c)) {
+            return Err(Error::invalid(format!("unknown segment '{}' in '{}'", c, s)));
+        }
         let mut chars: Vec<char> = s.chars().collect();
         chars.sort_unstable();
         Ok(SevenSegmentDisplay {
@@ -217,6 +226,7 @@ mod tests {
         let display = display.unwrap();
         assert_eq!(None, display.digit);
         assert_eq!("abcdefg", display.input);
+        assert!(SevenSegmentDisplay::from_str("abz").is_err());
     }
 
     #[test]
@@ -228,6 +238,8 @@ mod tests {
             SevenSegmentDisplay::from_str("be").unwrap(),
             entry.digits[0]
         );
+        assert!(Entry::from_str("be cfbegad | fdgacbe cefdb cefbgd gcbe").is_err());
+        assert!(Entry::from_str("be cfbegad").is_err());
     }
 
     #[test]
@@ -261,7 +273,7 @@ mod tests {
         let input =
             "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
         let mut entry: Entry = input.parse().unwrap();
-        assert_eq!(5353, entry.determine_digits());
+        assert_eq!(Ok(5353), entry.determine_digits());
     }
 
     #[test]
@@ -284,7 +296,7 @@ mod tests {
             61229usize,
             entries
                 .iter_mut()
-                .map(|entry| entry.determine_digits())
+                .map(|entry| entry.determine_digits().unwrap())
                 .sum()
         );
     }
@@ -2,76 +2,71 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let cave: Cave = input.parse().unwrap();
-    println!("Day 9 part 1: {}", cave.get_total_risk_level());
-    println!("Day 9 part 2: {}", cave.get_largest_basins_product(3));
+use crate::{
+    error::Error,
+    grid::{Grid, Neighbourhood},
+    image::{categorical, Draw, Image, BLACK},
+    parse::parse_digit,
+    solution::Solution,
+};
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Cave;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.get_total_risk_level())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.get_largest_basins_product(3))
+    }
 }
 
 pub struct Cave {
-    width: usize,
-    height: usize,
-    heightmap: Vec<u8>,
+    heightmap: Grid<u8>,
 }
 
 impl Cave {
-    fn get(&self, x: isize, y: isize) -> Option<u8> {
-        if x >= self.width as isize || y >= self.height as isize || y < 0 || x < 0 {
-            None
-        } else {
-            Some(self.heightmap[y as usize * self.width + x as usize])
-        }
+    pub fn get(&self, x: isize, y: isize) -> Option<u8> {
+        self.heightmap.get(x, y).copied()
     }
 
-    fn get_risk_levels(&self) -> Vec<u8> {
+    pub fn get_risk_levels(&self) -> Vec<u8> {
         self.get_low_points().iter().map(|v| v + 1).collect()
     }
 
     fn get_low_point_indices(&self) -> Vec<usize> {
-        let mut low_points = Vec::new();
-        for x in 0..(self.width as isize) {
-            for y in 0..(self.height as isize) {
-                let height = self.get(x, y).unwrap();
-                if self
-                    .get_neighbours(x, y)
-                    .iter()
-                    .map(|v| v.unwrap_or(u8::MAX))
-                    .all(|v| v > height)
-                {
-                    low_points.push(y as usize * self.width + x as usize);
-                }
-            }
-        }
-        low_points
+        (0..self.heightmap.len())
+            .filter(|&i| {
+                self.heightmap
+                    .get_neighbours(i, Neighbourhood::Four)
+                    .into_iter()
+                    .all(|neighbour| self.heightmap[neighbour] > self.heightmap[i])
+            })
+            .collect()
     }
 
-    fn get_low_points(&self) -> Vec<u8> {
+    pub fn get_low_points(&self) -> Vec<u8> {
         self.get_low_point_indices()
             .into_iter()
             .map(|v| self.heightmap[v])
             .collect()
     }
 
-    fn get_total_risk_level(&self) -> usize {
+    pub fn get_total_risk_level(&self) -> usize {
         self.get_risk_levels().iter().map(|&v| v as usize).sum()
     }
 
-    fn get_neighbours(&self, x: isize, y: isize) -> Vec<Option<u8>> {
-        vec![
-            self.get(x - 1, y),
-            self.get(x, y - 1),
-            self.get(x + 1, y),
-            self.get(x, y + 1),
-        ]
-    }
-
-    fn get_basins_recursive(&self) -> Vec<Option<usize>> {
-        let mut basins = vec![None; self.heightmap.len()];
+    fn get_basins_recursive(&self) -> Grid<Option<usize>> {
+        let mut basins = self.heightmap.map(|_| None);
         let low_points = self.get_low_point_indices();
         for (basin_id, start) in low_points.iter().enumerate() {
             self._get_basins_recursive(&mut basins, *start, basin_id);
@@ -81,7 +76,7 @@ impl Cave {
 
     fn _get_basins_recursive(
         &self,
-        basins: &mut Vec<Option<usize>>,
+        basins: &mut Grid<Option<usize>>,
         position: usize,
         basin_id: usize,
     ) {
@@ -90,25 +85,15 @@ impl Cave {
         }
 
         basins[position] = Some(basin_id);
-        let x = position % self.width;
-        let y = position / self.width;
-        if x > 0 {
-            self._get_basins_recursive(basins, position - 1, basin_id);
-        }
-        if x + 1 < self.width {
-            self._get_basins_recursive(basins, position + 1, basin_id);
-        }
-        if y > 0 {
-            self._get_basins_recursive(basins, position - self.width, basin_id);
-        }
-        if y + 1 < self.height {
-            self._get_basins_recursive(basins, position + self.width, basin_id);
+        for neighbour in self.heightmap.get_neighbours(position, Neighbourhood::Four) {
+            self._get_basins_recursive(basins, neighbour, basin_id);
         }
     }
 
-    fn get_largest_basins_product(&self, count: usize) -> usize {
+    pub fn get_largest_basins_product(&self, count: usize) -> usize {
         let basins = self.get_basins_recursive();
         let basin_sizes: Vec<usize> = basins
+            .get_cells()
             .iter()
             .filter(|v| v.is_some())
             .counts()
@@ -120,30 +105,22 @@ impl Cave {
 }
 
 impl FromStr for Cave {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let width = s
-            .lines()
-            .next()
-            .ok_or_else(|| "no line break".to_owned())?
-            .trim()
-            .len();
-        let height = s.trim().lines().count();
-        let heightmap: Vec<u8> = s
-            .chars()
-            .filter_map(|c| {
-                if c.is_numeric() {
-                    c.to_digit(10).map(|c| c as u8)
-                } else {
-                    None
-                }
-            })
-            .collect();
         Ok(Cave {
-            width,
-            height,
-            heightmap,
+            heightmap: Grid::parse(s, parse_digit)?,
+        })
+    }
+}
+
+/// Every basin in its own colour, the ridges of height 9 in black
+impl Draw for Cave {
+    fn draw(&self) -> Image {
+        // The modulo keeps the colours few enough for a GIF
+        let basins = self.get_basins_recursive();
+        Image::from_grid(basins.get_width(), basins.get_cells(), |basin| {
+            basin.map_or(BLACK, |id| categorical(id % 255))
         })
     }
 }
@@ -161,8 +138,10 @@ mod tests {
         let cave: Result<Cave, _> = input.parse();
         assert!(cave.is_ok());
         let cave: Cave = cave.unwrap();
-        assert_eq!(5, cave.height);
-        assert_eq!(10, cave.width);
+        assert_eq!(5, cave.heightmap.get_height());
+        assert_eq!(10, cave.heightmap.get_width());
+        let error = "219\n398\n9a5".parse::<Cave>().err().unwrap();
+        assert_eq!((Some(3), Some(2)), (error.line, error.column));
     }
 
     #[test]
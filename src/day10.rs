@@ -1,14 +1,23 @@
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    println!("Day 10 part 1: {}", Chunk::get_corrupted_high_score(&input));
-    println!(
-        "Day 10 part 2: {}",
-        Chunk::get_incomplete_high_score(&input)
-    );
+use crate::{error::Error, parse::parse_lines, solution::Solution};
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Vec<Token>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, |line| Token::parse_tokens(line.trim()))
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(Chunk::get_corrupted_high_score(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Chunk::get_incomplete_high_score(input)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -32,16 +41,22 @@ pub struct Token {
 }
 
 impl Token {
-    fn parse_tokens(input: &str) -> Vec<Token> {
-        input.chars().map(|c| c.into()).collect()
+    pub fn parse_tokens(input: &str) -> Result<Vec<Token>, Error> {
+        input
+            .chars()
+            .enumerate()
+            .map(|(i, c)| Token::try_from(c).map_err(|e| e.at_column(i + 1)))
+            .collect()
     }
 }
 
-impl From<char> for Token {
-    fn from(s: char) -> Self {
+impl TryFrom<char> for Token {
+    type Error = Error;
+
+    fn try_from(s: char) -> Result<Self, Self::Error> {
         use Direction::*;
         use ParenType::*;
-        match s {
+        Ok(match s {
             '(' => Token {
                 direction: Opening,
                 paren_type: Round,
@@ -74,8 +89,8 @@ impl From<char> for Token {
                 direction: Closing,
                 paren_type: Pointy,
             },
-            e => panic!("Illegal character: '{}'", e),
-        }
+            e => return Err(Error::invalid(format!("illegal character '{}'", e))),
+        })
     }
 }
 
@@ -94,7 +109,7 @@ pub struct Chunk {
 }
 
 impl Chunk {
-    fn parse(input: &[Token], position: &mut usize) -> Result<Chunk, ChunkError> {
+    pub fn parse(input: &[Token], position: &mut usize) -> Result<Chunk, ChunkError> {
         let opening_token = input[*position];
         if opening_token.direction != Direction::Opening {
             return Err(ChunkError::Corrupted(opening_token));
@@ -157,12 +172,10 @@ impl Chunk {
         }
     }
 
-    fn get_corrupted_high_score(input_lines: &str) -> usize {
+    pub fn get_corrupted_high_score(input_lines: &[Vec<Token>]) -> usize {
         input_lines
-            .trim()
-            .lines()
-            .map(|line| Token::parse_tokens(line.trim()))
-            .map(|tokens| Chunk::parse(&tokens, &mut 0))
+            .iter()
+            .map(|tokens| Chunk::parse(tokens, &mut 0))
             .filter_map(|chunk| match chunk {
                 Ok(_) => None,
                 Err(e) => match e {
@@ -173,12 +186,10 @@ impl Chunk {
             .sum()
     }
 
-    fn get_incomplete_high_score(input_lines: &str) -> usize {
+    pub fn get_incomplete_high_score(input_lines: &[Vec<Token>]) -> Result<usize, Error> {
         let mut scores: Vec<usize> = input_lines
-            .trim()
-            .lines()
-            .map(|line| Token::parse_tokens(line.trim()))
-            .map(|tokens| Chunk::parse(&tokens, &mut 0))
+            .iter()
+            .map(|tokens| Chunk::parse(tokens, &mut 0))
             .filter_map(|chunk| match chunk {
                 Ok(_) => None,
                 Err(e) => match e {
@@ -188,7 +199,10 @@ impl Chunk {
             })
             .collect();
         scores.sort_unstable();
-        scores[scores.len() / 2]
+        scores
+            .get(scores.len() / 2)
+            .copied()
+            .ok_or_else(|| Error::unsolvable("there are no incomplete lines"))
     }
 }
 
@@ -209,12 +223,12 @@ mod tests {
                 paren_type: ParenType::Round,
             },
         ];
-        assert_eq!(expected, Token::parse_tokens(input));
+        assert_eq!(Ok(expected), Token::parse_tokens(input));
     }
 
     #[test]
     fn it_parses_simple_chunks() {
-        let input = Token::parse_tokens("()");
+        let input = Token::parse_tokens("()").unwrap();
         let chunk = Chunk::parse(&input, &mut 0);
         assert!(chunk.is_ok());
         let chunk = chunk.unwrap();
@@ -226,7 +240,7 @@ mod tests {
         };
         assert_eq!(expected, chunk);
 
-        let input = Token::parse_tokens("({})");
+        let input = Token::parse_tokens("({})").unwrap();
         let chunk = Chunk::parse(&input, &mut 0).unwrap();
         expected.children.push(Chunk {
             paren_type: ParenType::Curly,
@@ -237,14 +251,14 @@ mod tests {
         expected.end = 3;
         assert_eq!(expected, chunk);
 
-        let input = Token::parse_tokens("({}<>)");
+        let input = Token::parse_tokens("({}<>)").unwrap();
         let chunk = Chunk::parse(&input, &mut 0).unwrap();
         assert_eq!(2, chunk.children.len());
     }
 
     #[test]
     fn it_finds_corrupted_chunks() {
-        let input = Token::parse_tokens("{([(<{}[<>[]}>{[]{[(<()>");
+        let input = Token::parse_tokens("{([(<{}[<>[]}>{[]{[(<()>").unwrap();
         let chunk = Chunk::parse(&input, &mut 0);
         assert!(chunk.is_err());
         let err = chunk.unwrap_err();
@@ -259,7 +273,7 @@ mod tests {
 
     #[test]
     fn it_finds_incomplete_chunks() {
-        let input = Token::parse_tokens("[({(<(())[]>[[{[]{<()<>>");
+        let input = Token::parse_tokens("[({(<(())[]>[[{[]{<()<>>").unwrap();
         let chunk = Chunk::parse(&input, &mut 0);
         assert!(chunk.is_err());
         let err = chunk.unwrap_err();
@@ -281,17 +295,18 @@ mod tests {
 [<(<(<(<{}))><([]([]()
 <{([([[(<>()){}]>(<<{{
 <{([{{}}[<[[[<>{}]]]>[]]";
-        assert_eq!(26_397, Chunk::get_corrupted_high_score(input));
+        let input = Day10::parse(input).unwrap();
+        assert_eq!(26_397, Chunk::get_corrupted_high_score(&input));
     }
 
     #[test]
     fn it_finds_missing_token() {
-        let input = Token::parse_tokens("[");
+        let input = Token::parse_tokens("[").unwrap();
         let chunk = Chunk::parse(&input, &mut 0);
         assert!(chunk.is_err());
         let err = chunk.unwrap_err();
         match err {
-            ChunkError::Corrupted(_) => assert!(false),
+            ChunkError::Corrupted(_) => panic!("it's corrupted instead of incomplete"),
             ChunkError::Incomplete(missing) => assert_eq!(
                 vec![Token {
                     paren_type: ParenType::Square,
@@ -304,13 +319,13 @@ mod tests {
 
     #[test]
     fn it_finds_missing_tokens_simple() {
-        let input = Token::parse_tokens("[(");
+        let input = Token::parse_tokens("[(").unwrap();
         let chunk = Chunk::parse(&input, &mut 0);
         assert!(chunk.is_err());
         let err = chunk.unwrap_err();
-        let expected = Token::parse_tokens(")]");
+        let expected = Token::parse_tokens(")]").unwrap();
         match err {
-            ChunkError::Corrupted(_) => assert!(false, "it's corrupted instead of incomplete"),
+            ChunkError::Corrupted(_) => panic!("it's corrupted instead of incomplete"),
             ChunkError::Incomplete(missing) => assert_eq!(expected, missing),
         };
     }
@@ -319,34 +334,36 @@ mod tests {
     fn it_rates_missing_tokens() {
         assert_eq!(
             288957,
-            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("}}]])})]")))
+            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("}}]])})]").unwrap()))
         );
         assert_eq!(
             5566,
-            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens(")}>]})")))
+            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens(")}>]})").unwrap()))
         );
         assert_eq!(
             1480781,
-            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("}}>}>))))")))
+            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("}}>}>))))").unwrap()))
         );
         assert_eq!(
             995444,
-            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("]]}}]}]}>")))
+            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("]]}}]}]}>").unwrap()))
         );
         assert_eq!(
             294,
-            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("])}>")))
+            Chunk::rate_error(ChunkError::Incomplete(Token::parse_tokens("])}>").unwrap()))
         );
     }
 
     #[test]
     fn it_finds_missing_tokens() {
-        let input = Token::parse_tokens("[({(<(())[]>[[{[]{<()<>>");
+        let input = Token::parse_tokens("[({(<(())[]>[[{[]{<()<>>").unwrap();
         let chunk = Chunk::parse(&input, &mut 0);
         let err = chunk.unwrap_err();
         match err {
-            ChunkError::Corrupted(_) => assert!(false, "it's corrupted instead of incomplete"),
-            ChunkError::Incomplete(inc) => assert_eq!(Token::parse_tokens("}}]])})]"), inc),
+            ChunkError::Corrupted(_) => panic!("it's corrupted instead of incomplete"),
+            ChunkError::Incomplete(inc) => {
+                assert_eq!(Token::parse_tokens("}}]])})]").unwrap(), inc)
+            }
         };
     }
 
@@ -362,6 +379,13 @@ mod tests {
 [<(<(<(<{}))><([]([]()
 <{([([[(<>()){}]>(<<{{
 <{([{{}}[<[[[<>{}]]]>[]]";
-        assert_eq!(288957, Chunk::get_incomplete_high_score(input));
+        let input = Day10::parse(input).unwrap();
+        assert_eq!(Ok(288957), Chunk::get_incomplete_high_score(&input));
+    }
+
+    #[test]
+    fn it_rejects_illegal_characters() {
+        let error = Day10::parse("[()]\n[(x)]").unwrap_err();
+        assert_eq!((Some(2), Some(3)), (error.line, error.column));
     }
 }
@@ -1,26 +1,41 @@
-use std::str::FromStr;
-
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let octopuses: Octopuses = input.parse().unwrap();
-    println!("Day 11 part 1: {}", octopuses.take(100).sum::<usize>());
-    let mut octopuses: Octopuses = input.parse().unwrap();
-    println!("Day 11 part 2: {}", octopuses.get_simultaneous_flash());
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    error::Error,
+    grid::{Grid, Neighbourhood},
+    image::{gradient, Draw, Image, WHITE},
+    parse::parse_digit,
+    playback::Simulation,
+    solution::Solution,
+};
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Octopuses;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.clone().take(100).sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.clone().get_simultaneous_flash())
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Octopuses {
-    width: usize,
-    height: usize,
-    octopuses: Vec<u8>,
+    octopuses: Grid<u8>,
 }
 
 impl Octopuses {
-    fn get_simultaneous_flash(&mut self) -> usize {
+    pub fn get_simultaneous_flash(&mut self) -> usize {
         self
             .enumerate()
             .take_while(|(_, flashes)| *flashes < 100)
@@ -30,12 +45,13 @@ impl Octopuses {
             + 2
     }
 
-    fn tick(&mut self) -> usize {
+    pub fn tick(&mut self) -> usize {
         let mut total_flashes = 0;
         self.increase_all();
-        while self.octopuses.iter().any(|&octopus| octopus > 9) {
+        while self.octopuses.get_cells().iter().any(|&octopus| octopus > 9) {
             let flashes: Vec<usize> = self
                 .octopuses
+                .get_cells_mut()
                 .iter_mut()
                 .enumerate()
                 .filter(|(_, o)| **o > 9)
@@ -46,7 +62,10 @@ impl Octopuses {
                 .collect();
             total_flashes += flashes.len();
             for flash_index in flashes {
-                for neighbour in self.get_neighbour_indices(flash_index) {
+                for neighbour in self
+                    .octopuses
+                    .get_neighbours(flash_index, Neighbourhood::Eight)
+                {
                     if self.octopuses[neighbour] != 0 {
                         self.octopuses[neighbour] += 1;
                     }
@@ -56,23 +75,8 @@ impl Octopuses {
         total_flashes
     }
 
-    fn get_neighbour_indices(&self, i: usize) -> Vec<usize> {
-        let x = i % self.width;
-        let y = i / self.width;
-        let mut neighbours = Vec::new();
-        for x_n in x.checked_sub(1).unwrap_or(x)..=(x+1).min(self.width-1) {
-            for y_n in y.checked_sub(1).unwrap_or(y)..=(y+1).min(self.height-1) {
-                if x_n == x && y_n == y {
-                    continue;
-                }
-                neighbours.push(y_n * self.width + x_n);
-            }
-        }
-        neighbours
-    }
-
     fn increase_all(&mut self) {
-        for octopus in &mut self.octopuses {
+        for octopus in self.octopuses.get_cells_mut() {
             *octopus += 1;
         }
     }
@@ -87,25 +91,43 @@ impl Iterator for Octopuses {
 }
 
 impl FromStr for Octopuses {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim();
         Ok(Octopuses {
-            width: s
-                .lines()
-                .next()
-                .ok_or_else(|| "No line breaks".to_owned())?
-                .len(),
-            height: s.lines().count(),
-            octopuses: s
-                .chars()
-                .filter_map(|c| c.to_digit(10).map(|v| v as u8))
-                .collect(),
+            octopuses: Grid::parse(s, parse_digit)?,
+        })
+    }
+}
+
+impl Display for Octopuses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.octopuses)
+    }
+}
+
+/// The octopuses that just flashed in white, the others darker the less energy they have
+impl Draw for Octopuses {
+    fn draw(&self) -> Image {
+        let (width, octopuses) = (self.octopuses.get_width(), self.octopuses.get_cells());
+        Image::from_grid(width, octopuses, |&energy| match energy {
+            0 => WHITE,
+            _ => gradient([0, 0, 64], [255, 160, 0], energy as usize, 9),
         })
     }
 }
 
+/// Runs until all octopuses flash at once
+impl Simulation for Octopuses {
+    fn step(&mut self) -> bool {
+        if self.octopuses.get_cells().iter().all(|&octopus| octopus == 0) {
+            return false;
+        }
+        self.tick();
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Octopuses;
@@ -120,8 +142,8 @@ mod tests {
         let octopuses: Result<Octopuses, _> = input.parse();
         assert!(octopuses.is_ok());
         let octopuses = octopuses.unwrap();
-        assert_eq!(5, octopuses.width);
-        assert_eq!(5, octopuses.height);
+        assert_eq!(5, octopuses.octopuses.get_width());
+        assert_eq!(5, octopuses.octopuses.get_height());
     }
 
     #[test]
@@ -5,20 +5,36 @@ use std::{
     str::FromStr,
 };
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut caves: Caves = input.parse().unwrap();
-    let neighbours = Caves::get_neighbours(&input);
-    println!("Day 12 part 1: {}", caves.find_paths_start_to_end(&neighbours));
-    let mut caves: Caves = input.parse().unwrap();
-    println!(
-        "Day 12 part 2: {}",
-        caves.find_paths_start_to_end_with_extra_time(&neighbours)
-    );
+use crate::{error::Error, parse::parse_lines, solution::Solution};
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = (Caves, HashMap<Cave, HashSet<Cave>>);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let neighbours = Caves::get_neighbours(input)?;
+        for name in ["start", "end"] {
+            if !neighbours.contains_key(&Cave::new(name)) {
+                return Err(Error::invalid(format!("there is no cave called '{}'", name)));
+            }
+        }
+        Ok((input.parse()?, neighbours))
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let (caves, neighbours) = input;
+        Ok(caves.clone().find_paths_start_to_end(neighbours))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        let (caves, neighbours) = input;
+        Ok(caves
+            .clone()
+            .find_paths_start_to_end_with_extra_time(neighbours))
+    }
 }
 
 #[derive(Clone)]
@@ -44,13 +60,13 @@ impl PartialEq for Cave {
     }
 }
 impl Cave {
-    fn new(name: &str) -> Cave {
+    pub fn new(name: &str) -> Cave {
         let is_small = name
             .chars()
             .next()
             .map(|c| c.is_lowercase())
             .unwrap_or(false);
-        let can_visit_again = !vec!["start", "end"].contains(&name);
+        let can_visit_again = !["start", "end"].contains(&name);
         Cave {
             name: name.to_owned(),
             //is_small,
@@ -83,13 +99,13 @@ impl Display for Edge {
 }
 
 impl FromStr for Edge {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (from, to) = s
             .trim()
             .split_once('-')
-            .ok_or_else(|| "No minus in line".to_owned())?;
+            .ok_or_else(|| Error::invalid("no minus in line"))?;
         Ok(Edge(Cave::new(from), Cave::new(to)))
     }
 }
@@ -101,12 +117,8 @@ pub struct Caves {
 
 impl Caves {
 
-    fn get_neighbours(s: &str) ->  HashMap<Cave, HashSet<Cave>> {
-        let mut edges: Vec<Edge> = s
-            .trim()
-            .lines()
-            .map(Edge::from_str)
-            .collect::<Result<Vec<Edge>, String>>().unwrap();
+    pub fn get_neighbours(s: &str) -> Result<HashMap<Cave, HashSet<Cave>>, Error> {
+        let mut edges: Vec<Edge> = parse_lines(s, Edge::from_str)?;
         // we want bi-directional edges
         for edge in edges.clone() {
             edges.push(Edge(edge.1, edge.0));
@@ -118,17 +130,17 @@ impl Caves {
                 .and_modify(|entry| {
                     entry.insert(edge.1.clone());
                 })
-                .or_insert_with(HashSet::new)
+                .or_default()
                 .insert(edge.1);
         }
 
-        neighbours
+        Ok(neighbours)
     }
-    fn find_paths_start_to_end(&mut self, neighbours: &HashMap<Cave, HashSet<Cave>>) -> usize {
+    pub fn find_paths_start_to_end(&mut self, neighbours: &HashMap<Cave, HashSet<Cave>>) -> usize {
         self.find_paths(&Cave::new("start"), &Cave::new("end"), false, neighbours)
     }
 
-    fn find_paths_start_to_end_with_extra_time(&mut self, neighbours: &HashMap<Cave, HashSet<Cave>>) -> usize {
+    pub fn find_paths_start_to_end_with_extra_time(&mut self, neighbours: &HashMap<Cave, HashSet<Cave>>) -> usize {
         self.find_paths(&Cave::new("start"), &Cave::new("end"), true, neighbours)
     }
 
@@ -140,9 +152,11 @@ impl Caves {
            self.visited.insert(from.clone());
         }
         let mut paths = 0;
-        for next_cave in neighbours[from]
-            .iter()
-            .filter(|cave| (!self.visited.contains(cave) || (extra_time && cave.can_visit_again)))
+        for next_cave in neighbours
+            .get(from)
+            .into_iter()
+            .flatten()
+            .filter(|cave| !self.visited.contains(cave) || (extra_time && cave.can_visit_again) )
         {
             let mut cloned_self = self.clone();
             paths += if self.visited.contains(next_cave) {
@@ -156,14 +170,10 @@ impl Caves {
 }
 
 impl FromStr for Caves {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut edges: Vec<Edge> = s
-            .trim()
-            .lines()
-            .map(Edge::from_str)
-            .collect::<Result<Vec<Edge>, String>>()?;
+        let mut edges: Vec<Edge> = parse_lines(s, Edge::from_str)?;
         // we want bi-directional edges
         for edge in edges.clone() {
             edges.push(Edge(edge.1, edge.0));
@@ -175,7 +185,7 @@ impl FromStr for Caves {
                 .and_modify(|entry| {
                     entry.insert(edge.1.clone());
                 })
-                .or_insert_with(HashSet::new)
+                .or_default()
                 .insert(edge.1);
         }
         Ok(Caves {
@@ -197,6 +207,12 @@ mod tests {
         assert_ne!(cave1, cave3);
     }
 
+    #[test]
+    fn it_rejects_invalid_caves() {
+        assert_eq!(Some(2), Day12::parse("start-A\nA+end").err().unwrap().line);
+        assert!(Day12::parse("start-A\nA-b").is_err());
+    }
+
 
     #[test]
     fn it_finds_paths() {
@@ -208,7 +224,7 @@ mod tests {
         A-end
         b-end";
         let mut caves: Caves = input.parse().unwrap();
-        let neighbours = Caves::get_neighbours(&input);
+        let neighbours = Caves::get_neighbours(input).unwrap();
         let paths = caves.find_paths_start_to_end(&neighbours);
         assert_eq!(10, paths);
 
@@ -223,7 +239,7 @@ mod tests {
         kj-HN
         kj-dc";
         let mut caves: Caves = input.parse().unwrap();
-        let neighbours = Caves::get_neighbours(&input);
+        let neighbours = Caves::get_neighbours(input).unwrap();
         assert_eq!(19, caves.find_paths_start_to_end(&neighbours));
 
         let input = "fs-end
@@ -247,7 +263,7 @@ mod tests {
         let mut caves: Caves = input
             .parse()
             .unwrap();
-        let neighbours = Caves::get_neighbours(&input);
+        let neighbours = Caves::get_neighbours(input).unwrap();
         assert_eq!(226, caves.find_paths_start_to_end(&neighbours));
     }
 
@@ -261,7 +277,7 @@ mod tests {
         A-end
         b-end";
         let mut caves: Caves = input.parse().unwrap();
-        let neighbours = Caves::get_neighbours(&input);
+        let neighbours = Caves::get_neighbours(input).unwrap();
         let paths = caves.find_paths_start_to_end_with_extra_time(&neighbours);
         assert_eq!(36, paths);
 
@@ -276,7 +292,7 @@ mod tests {
         kj-HN
         kj-dc";
         let mut caves: Caves = input.parse().unwrap();
-        let neighbours = Caves::get_neighbours(&input);
+        let neighbours = Caves::get_neighbours(input).unwrap();
         assert_eq!(103, caves.find_paths_start_to_end_with_extra_time(&neighbours));
 
         let input = "fs-end
@@ -300,7 +316,7 @@ mod tests {
         let mut caves: Caves = input
             .parse()
             .unwrap();
-        let neighbours = Caves::get_neighbours(&input);
+        let neighbours = Caves::get_neighbours(input).unwrap();
         assert_eq!(3509, caves.find_paths_start_to_end_with_extra_time(&neighbours));
     }
 }
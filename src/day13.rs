@@ -2,162 +2,179 @@ use std::{collections::HashSet, fmt::Display, str::FromStr};
 
 use itertools::Itertools;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut paper: Paper = input.parse().unwrap();
-    paper.apply_fold(paper.folds[0].clone());
-    println!("Day 13 part 1: {}", paper.points.len());
-    let mut paper: Paper = input.parse().unwrap();
-    paper.fold();
-    println!("Day 13 part 2: \n{}", paper);
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Point(usize, usize);
+use crate::{
+    error::Error,
+    geometry::{Rectangle, Vector2},
+    parse::parse_lines,
+    playback::Simulation,
+    solution::Solution,
+};
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Paper;
+    type Answer1 = usize;
+    type Answer2 = Paper;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
 
-impl FromStr for Point {
-    type Err = &'static str;
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let mut paper = input.clone();
+        let fold = paper
+            .folds
+            .first()
+            .ok_or_else(|| Error::unsolvable("there is no fold"))?
+            .clone();
+        paper.apply_fold(fold)?;
+        Ok(paper.get_points().len())
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s
-            .trim()
-            .split_once(',')
-            .ok_or("cannot split Point by comma")?;
-        Ok(Point(
-            x.parse().map_err(|_| "cannot parse x coordinate")?,
-            y.parse().map_err(|_| "cannot parse y coordinate")?,
-        ))
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        let mut paper = input.clone();
+        paper.fold()?;
+        Ok(paper)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Fold(Option<usize>, Option<usize>);
+pub enum Fold {
+    X(usize),
+    Y(usize),
+}
 
 impl FromStr for Fold {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s
-            .trim()
-            .split_ascii_whitespace()
-            .nth(2)
-            .ok_or("cannot parse fold instruction")?;
         let (axis, value) = s
             .trim()
+            .strip_prefix("fold along ")
+            .ok_or_else(|| Error::invalid("fold instruction must start with 'fold along '"))?
             .split_once('=')
-            .ok_or("cannot parse fold instruction value")?;
-        if axis == "x" {
-            Ok(Fold(
-                Some(value.parse().map_err(|_| "Cannot parse fold value")?),
-                None,
-            ))
-        } else {
-            Ok(Fold(
-                None,
-                Some(value.parse().map_err(|_| "Cannot parse fold value")?),
-            ))
+            .ok_or_else(|| Error::invalid("cannot parse fold instruction value"))?;
+        match axis {
+            "x" => Ok(Fold::X(value.parse()?)),
+            "y" => Ok(Fold::Y(value.parse()?)),
+            _ => Err(Error::invalid(format!("unknown axis '{}'", axis))),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Paper {
-    points: HashSet<Point>,
+    points: HashSet<Vector2>,
     folds: Vec<Fold>,
 }
 
 impl Paper {
-    fn fold(&mut self) {
+    pub fn get_points(&self) -> &HashSet<Vector2> {
+        &self.points
+    }
+
+    pub fn fold(&mut self) -> Result<(), Error> {
         let folds: Vec<Fold> = self.folds.to_vec();
         for fold in folds {
-            self.apply_fold(fold);
+            self.apply_fold(fold)?;
         }
+        Ok(())
     }
 
-    fn apply_fold(&mut self, fold: Fold) {
-        match fold {
-            Fold(Some(x), None) => {
-                let mut points = HashSet::new();
-                for Point(px, py) in self.points.drain() {
-                    if px > x {
-                        points.insert(Point(x - (px - x), py));
-                    } else {
-                        points.insert(Point(px, py));
-                    }
-                }
-                self.points = points;
-            }
-            Fold(None, Some(y)) => {
-                let mut points = HashSet::new();
-                for Point(px, py) in self.points.drain() {
-                    if py > y {
-                        points.insert(Point(px, y - (py - y)));
-                    } else {
-                        points.insert(Point(px, py));
-                    }
+    /// Folds the paper once. On error, the paper is left as it was.
+    pub fn apply_fold(&mut self, fold: Fold) -> Result<(), Error> {
+        let (axis, line) = match fold {
+            Fold::X(x) => (0, x as isize),
+            Fold::Y(y) => (1, y as isize),
+        };
+        let mut points = HashSet::new();
+        for mut point in self.points.iter().copied() {
+            if point[axis] > line {
+                let mirrored = 2 * line - point[axis];
+                if mirrored < 0 {
+                    return Err(Error::unsolvable(format!(
+                        "folding at {} moves {} beyond the edge of the paper",
+                        line, point[axis]
+                    )));
                 }
-                self.points = points;
+                point[axis] = mirrored;
             }
-            _ => panic!(),
+            points.insert(point);
         }
+        self.points = points;
+        Ok(())
     }
 }
 
 impl FromStr for Paper {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (points, folds) = s
             .trim()
             .split_once("\n\n")
-            .ok_or("Error splitting input in points and folds")?;
-        let points = points
-            .lines()
-            .map(|point| point.parse())
-            .collect::<Result<HashSet<Point>, &'static str>>()?;
-        let folds = folds
-            .lines()
-            .map(|fold| fold.parse())
-            .collect::<Result<Vec<Fold>, &'static str>>()?;
+            .ok_or_else(|| Error::invalid("cannot split input into points and folds"))?;
+        let offset = points.lines().count() + 1;
+        let points: HashSet<Vector2> = parse_lines(points, Vector2::from_str)?
+            .into_iter()
+            .collect();
+        if let Some(point) = points.iter().find(|p| p.x() < 0 || p.y() < 0) {
+            return Err(Error::invalid(format!(
+                "point {} is not on the paper",
+                point
+            )));
+        }
+        let folds = parse_lines(folds, Fold::from_str).map_err(|e| e.below(offset))?;
         Ok(Paper { points, folds })
     }
 }
 
 impl Display for Paper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let max_x = self.points.iter().map(|p| p.0).max().unwrap() + 1;
-        let max_y = self.points.iter().map(|p| p.1).max().unwrap() + 1;
-        let mut grid: Vec<Vec<bool>> = vec![vec![false; max_y]; max_x];
+        // Points are never negative, so the paper starts at the origin
+        let max = Rectangle::enclosing(self.points.iter().copied())
+            .map_or(Vector2::new(-1, -1), |bounds| bounds.max);
+        let mut grid = vec![vec![false; (max.x() + 1) as usize]; (max.y() + 1) as usize];
         for point in &self.points {
-            grid[point.0][point.1] = true;
+            grid[point.y() as usize][point.x() as usize] = true;
         }
-        for y in 0..max_y {
+        for row in grid {
             writeln!(
                 f,
                 "{}",
-                (0..max_x)
-                    .map(|x| if grid[x][y] { "#" } else { "." })
-                    .join("")
+                row.iter().map(|&v| if v { "#" } else { "." }).join("")
             )?;
         }
         Ok(())
     }
 }
 
+/// Applies the folds one at a time
+impl Simulation for Paper {
+    fn step(&mut self) -> bool {
+        if self.folds.is_empty() {
+            return false;
+        }
+        // The fold is only used up once it worked
+        if self.apply_fold(self.folds[0].clone()).is_err() {
+            return false;
+        }
+        self.folds.remove(0);
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn it_parses_point() {
-        let point: Result<Point, _> = "6,10".parse();
+        let point: Result<Vector2, _> = "6,10".parse();
         assert!(point.is_ok());
         let point = point.unwrap();
-        assert_eq!(Point(6, 10), point);
+        assert_eq!(Vector2::new(6, 10), point);
     }
 
     #[test]
@@ -165,19 +182,21 @@ mod tests {
         let fold: Result<Fold, _> = "fold along y=7".parse();
         assert!(fold.is_ok());
         let fold = fold.unwrap();
-        assert_eq!(Fold(None, Some(7)), fold);
+        assert_eq!(Fold::Y(7), fold);
+        assert!("fold along z=7".parse::<Fold>().is_err());
+        assert!("fold y=7".parse::<Fold>().is_err());
     }
 
     #[test]
     fn it_folds() {
         let fold: Fold = "fold along y=5".parse().unwrap();
-        let point = Point(2, 7);
+        let point = Vector2::new(2, 7);
         let mut paper: Paper = Paper {
             points: [point].iter().cloned().collect(),
             folds: vec![fold.clone()],
         };
-        paper.apply_fold(fold);
-        assert_eq!(Point(2, 3), paper.points.iter().next().unwrap().clone());
+        paper.apply_fold(fold).unwrap();
+        assert_eq!(Vector2::new(2, 3), paper.points.iter().next().unwrap().clone());
     }
 
     #[test]
@@ -203,8 +222,25 @@ mod tests {
 
 fold along y=7";
         let mut paper: Paper = input.parse().unwrap();
-        paper.fold();
+        paper.fold().unwrap();
         assert_eq!(17, paper.points.len());
         println!("{}", paper);
     }
+
+    #[test]
+    fn it_reports_invalid_folds() {
+        let error = "6,10\n0,14\n\nfold along y=7\nfold along q=3"
+            .parse::<Paper>()
+            .unwrap_err();
+        assert_eq!(Some(5), error.line);
+        let mut paper: Paper = "6,10\n\nfold along y=3".parse().unwrap();
+        assert!(paper.fold().is_err());
+        // A failed step leaves the paper and the fold as they were
+        let mut paper: Paper = "1,1\n6,10\n\nfold along y=3".parse().unwrap();
+        assert!(!paper.step());
+        assert_eq!(2, paper.get_points().len());
+        assert!(paper.get_points().contains(&Vector2::new(6, 10)));
+        assert_eq!(vec![Fold::Y(3)], paper.folds);
+        assert!("-1,3\n\nfold along y=1".parse::<Paper>().is_err());
+    }
 }
@@ -2,41 +2,55 @@ use std::{collections::HashMap, str::FromStr};
 
 use itertools::Itertools;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut polymers: Polymers = input.parse().unwrap();
-    polymers.nth(9);
-    println!("Day 14 part 1: {}", polymers.get_max_minus_min());
-    let polymers: Polymers = input.parse().unwrap();
-    println!("Day 14 part 2: {}", polymers.moritz_idea(40));
+use crate::{error::Error, parse::parse_lines, solution::Solution};
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Polymers;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let mut polymers = input.clone();
+        polymers.nth(9);
+        Ok(polymers.get_max_minus_min())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.moritz_idea(40))
+    }
 }
 
+#[derive(Clone)]
 pub struct Polymers {
     elements: Vec<char>,
     pair_insertions: HashMap<(char, char), char>
 }
 
 impl Polymers {
-    fn get_max_minus_min(&self) -> usize {
+    pub fn get_max_minus_min(&self) -> usize {
         let counts = self.elements.iter().counts();
-        counts.values().max().unwrap() - counts.values().min().unwrap()
+        counts.values().max().unwrap_or(&0) - counts.values().min().unwrap_or(&0)
     }
 
-    fn moritz_idea(&self, iterations: usize) -> usize {
+    pub fn moritz_idea(&self, iterations: usize) -> usize {
         let mut pair_counts: HashMap<(char, char), usize> = HashMap::new();
         let mut char_counts: HashMap<char, usize> = HashMap::new();
         for c in 'A'..='Z' {
             char_counts.insert(c, 0);
         }
-        for i in 0..self.elements.len() - 1{
+        for i in 0..self.elements.len().saturating_sub(1) {
             pair_counts.entry((self.elements[i], self.elements[i+1])).and_modify(|v| *v += 1).or_insert(1);
             char_counts.entry(self.elements[i]).and_modify(|v| *v += 1);
         }
-        char_counts.entry(*self.elements.last().unwrap()).and_modify(|v| *v += 1);
+        if let Some(last) = self.elements.last() {
+            char_counts.entry(*last).and_modify(|v| *v += 1);
+        }
 
         for _ in 0..iterations {
             let mut result = HashMap::new();
@@ -58,35 +72,48 @@ impl Iterator for Polymers {
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut new_elements = Vec::new();
-        for i in 0..self.elements.len()-1 {
+        for i in 0..self.elements.len().saturating_sub(1) {
             let pair = (self.elements[i], self.elements[i+1]);
             new_elements.push(self.elements[i]);
             if let Some(inserted) = self.pair_insertions.get(&pair) {
                 new_elements.push(*inserted);
             }
         }
-        new_elements.push(*self.elements.last().unwrap());
+        new_elements.extend(self.elements.last());
         self.elements = new_elements;
         Some(())
     }
 }
 
 impl FromStr for Polymers {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (template, rules) = s.trim().split_once("\n\n").ok_or("cannot split input into 2")?;
+        let (template, rules) = s
+            .trim()
+            .split_once("\n\n")
+            .ok_or_else(|| Error::invalid("cannot split input into template and rules"))?;
         let elements: Vec<char> = template.trim().chars().collect();
-        let mut pair_insertions = HashMap::new();
-        for rule in rules.trim().lines() {
-            let (input, c) = rule.trim().split_once(" -> ").ok_or("cannot parse pair_insertion")?;
-            let mut chars = input.chars();
-            let (c1, c2) = (chars.next().ok_or("cannot parse char 1")?, chars.next().ok_or("cannot parse char 2")?);
-            let inserted = c.chars().next().ok_or("cannot parse inserted char")?;
-            pair_insertions.insert((c1, c2), inserted);
+        if elements.is_empty() || !elements.iter().all(char::is_ascii_uppercase) {
+            return Err(Error::invalid("the template must consist of letters A to Z").at_line(1));
         }
+        let rules = parse_lines(rules, |rule| {
+            let (input, c) = rule
+                .trim()
+                .split_once(" -> ")
+                .ok_or_else(|| Error::invalid("cannot split pair insertion at '->'"))?;
+            let pair: Vec<char> = input.chars().collect();
+            let inserted: Vec<char> = c.chars().collect();
+            match (&pair[..], &inserted[..]) {
+                (&[c1, c2], &[inserted]) if inserted.is_ascii_uppercase() => {
+                    Ok(((c1, c2), inserted))
+                }
+                _ => Err(Error::invalid(format!("invalid pair insertion '{}'", rule.trim()))),
+            }
+        })
+        .map_err(|e| e.below(template.lines().count() + 1))?;
+        let pair_insertions = rules.into_iter().collect();
         Ok(Polymers {elements, pair_insertions})
-
     }
 }
 
@@ -122,6 +149,14 @@ mod tests {
         assert_eq!(16, polymers.pair_insertions.len());
     }
 
+    #[test]
+    fn it_rejects_invalid_rules() {
+        let error = "NNCB\n\nCH -> B\nHH => N".parse::<Polymers>().err().unwrap();
+        assert_eq!(Some(4), error.line);
+        assert!("NNCB\n\nCHH -> B".parse::<Polymers>().is_err());
+        assert!("\n\nCH -> B".parse::<Polymers>().is_err());
+    }
+
     #[test]
     fn it_steps() {
         let input = "NNCB
@@ -1,110 +1,299 @@
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let cave: Cave = input.parse().unwrap();
-    let end = cave.risk.len() - 1;
-    println!("Day 15 part 1: {}", cave.find_path(0, end));
-    let cave = Cave::from_tile(&cave, 5);
-    let end = cave.risk.len() - 1;
-    println!("Day 15 part 2: {}", cave.find_path(0, end));
-}
+use std::{fmt::Display, str::FromStr, sync::Arc};
 
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
+use crate::{
+    error::Error,
+    grid::{Grid, Neighbourhood},
+    image::{gradient, Draw, Image},
+    parse::parse_digit,
+    search::a_star,
+    solution::Solution,
 };
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Cave;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        input
+            .find_path((0, 0), input.get_end())
+            .ok_or_else(|| Error::unsolvable("the end cannot be reached"))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        let cave = Cave::from_tile(input, 5)?;
+        cave.find_path((0, 0), cave.get_end())
+            .ok_or_else(|| Error::unsolvable("the end cannot be reached"))
+    }
+}
+
+/// What a move costs on top of the risk of the position it enters, by its offset
+pub type MoveCost = Arc<dyn Fn((isize, isize)) -> usize + Send + Sync>;
+
+#[derive(Clone)]
 pub struct Cave {
-    risk: Vec<usize>,
-    neighbours: HashMap<usize, Vec<usize>>,
+    /// The risk of one tile
+    risk: Grid<usize>,
+    tiling: Tiling,
+    /// The offsets of the positions one move away
+    moves: Vec<(isize, isize)>,
+    move_cost: Option<MoveCost>,
+}
+
+/// How the tile repeats to the right and down. Every tile further away adds 1 to the risk, and
+/// risks above the maximum wrap around to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tiling {
+    /// How many tiles across and down
+    factor: usize,
+    max_risk: usize,
 }
+
+impl Tiling {
+    /// Fails if there are no tiles, or no risk to wrap around to
+    pub fn new(factor: usize, max_risk: usize) -> Result<Tiling, Error> {
+        if factor == 0 {
+            return Err(Error::invalid("a cave needs at least 1 tile"));
+        }
+        if max_risk == 0 {
+            return Err(Error::invalid("the maximum risk must be at least 1"));
+        }
+        Ok(Tiling { factor, max_risk })
+    }
+
+    pub fn get_factor(&self) -> usize {
+        self.factor
+    }
+
+    pub fn get_max_risk(&self) -> usize {
+        self.max_risk
+    }
+}
+
+impl Default for Tiling {
+    /// A single tile
+    fn default() -> Self {
+        Tiling {
+            factor: 1,
+            max_risk: 9,
+        }
+    }
+}
+
+/// The way with the lowest total risk between two positions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// The risk of every position entered, so without the start, and the cost of the moves
+    pub risk: usize,
+    /// `(x, y)` of the positions on the way, from the start to the end, both included
+    pub positions: Vec<(usize, usize)>,
+}
+
 impl Cave {
-    fn find_path(&self, start: usize, end: usize) -> usize {
-        let mut distances: HashMap<usize, usize> = HashMap::new();
-        let mut visited: HashSet<usize> = HashSet::new();
-        distances.insert(start, 0);
-        while !distances.is_empty() {
-            let (&u, &dist) = distances.iter().min_by_key(|(_, v)| **v).unwrap();
-            visited.insert(u);
-            distances.remove(&u);
-            if u == end {
-                return dist;
-            }
-            if let Some(neighbours) = self.neighbours.get(&u) {
-                for neighbour in neighbours {
-                    if !visited.contains(neighbour) {
-                        let alternative = dist + self.risk[*neighbour];
-                        if alternative < *distances.get(neighbour).unwrap_or(&(usize::max as usize))
-                        {
-                            distances.insert(*neighbour, alternative);
-                        }
-                    }
-                }
-            }
+    pub fn get_width(&self) -> usize {
+        self.risk.get_width() * self.tiling.factor
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.risk.get_height() * self.tiling.factor
+    }
+
+    /// `(x, y)` of the bottom right position
+    pub fn get_end(&self) -> (usize, usize) {
+        (self.get_width() - 1, self.get_height() - 1)
+    }
+
+    /// The risk at a position of the tiled cave, worked out from the tile
+    pub fn get_risk(&self, (x, y): (usize, usize)) -> usize {
+        let (width, height) = (self.risk.get_width(), self.risk.get_height());
+        let risk = self.risk[(x % width, y % height)] + x / width + y / height;
+        match risk > self.tiling.max_risk {
+            true => (risk - 1) % self.tiling.max_risk + 1,
+            false => risk,
         }
-        panic!("Didn't arrive at end");
     }
 
-    fn set_neighbours(&mut self) {
-        let width = (self.risk.len() as f64).sqrt() as usize;
-        for i in 0..self.risk.len() {
-            let x = i % width;
-            let y = i / width;
-            let mut n = Vec::new();
-            if x > 0 {
-                n.push(i - 1);
-            }
-            if x + 1 < width {
-                n.push(i + 1);
-            }
-            if y > 0 {
-                n.push(i - width);
-            }
-            if y + 1 < width {
-                n.push(i + width);
+    fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.get_width() && y < self.get_height()
+    }
+
+    /// Moves to the cells around a position, the four next to it by default
+    pub fn with_neighbourhood(self, neighbourhood: Neighbourhood) -> Cave {
+        Cave {
+            moves: neighbourhood.get_offsets().to_vec(),
+            ..self
+        }
+    }
+
+    /// Any moves, e.g. the knight moves of chess, as offsets from the position. Fails without
+    /// any moves, or with one that stays in place.
+    pub fn with_moves(self, moves: Vec<(isize, isize)>) -> Result<Cave, Error> {
+        if moves.is_empty() {
+            return Err(Error::invalid("there must be at least 1 move"));
+        }
+        if moves.contains(&(0, 0)) {
+            return Err(Error::invalid("the move (0, 0) does not go anywhere"));
+        }
+        Ok(Cave { moves, ..self })
+    }
+
+    /// Adds a cost to every move, on top of the risk
+    pub fn with_move_cost(
+        self,
+        move_cost: impl Fn((isize, isize)) -> usize + Send + Sync + 'static,
+    ) -> Cave {
+        Cave {
+            move_cost: Some(Arc::new(move_cost)),
+            ..self
+        }
+    }
+
+    /// The positions one move away, with what it costs to move there
+    fn get_neighbours(
+        &self,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), usize)> + '_ {
+        self.moves.iter().filter_map(move |&(dx, dy)| {
+            let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            if !self.contains(next) {
+                return None;
             }
-            self.neighbours.insert(i, n);
+            let move_cost = self.move_cost.as_ref().map_or(0, |cost| cost((dx, dy)));
+            Some((next, self.get_risk(next) + move_cost))
+        })
+    }
+
+    pub fn find_path(&self, start: (usize, usize), end: (usize, usize)) -> Option<usize> {
+        self.find_route(start, end).map(|route| route.risk)
+    }
+
+    /// The route with the lowest total risk from `start` to `end`, `None` if one of them is
+    /// outside of the cave or the end cannot be reached
+    pub fn find_route(&self, start: (usize, usize), end: (usize, usize)) -> Option<Route> {
+        if !self.contains(start) || !self.contains(end) {
+            return None;
+        }
+        // Every move costs at least the lowest risk, and gets at most the longest move closer to
+        // the end, so A* never overestimates with those. Risks above the maximum already wrap
+        // around in the first tile, and further tiles can wrap around to 1.
+        let (width, height) = (self.risk.get_width(), self.risk.get_height());
+        let mut lowest_risk = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|position| self.get_risk(position))
+            .min()
+            .unwrap_or(0);
+        if self.tiling.factor > 1 {
+            lowest_risk = lowest_risk.min(1);
         }
+        let longest_move = self
+            .moves
+            .iter()
+            .map(|(dx, dy)| dx.unsigned_abs() + dy.unsigned_abs())
+            .max()
+            .unwrap_or(1);
+        let path = a_star(
+            start,
+            |&position| self.get_neighbours(position),
+            |&(x, y)| {
+                let distance = x.abs_diff(end.0) + y.abs_diff(end.1);
+                distance.div_ceil(longest_move) * lowest_risk
+            },
+            |&position| position == end,
+        )?;
+        Some(Route {
+            risk: path.cost,
+            positions: path.nodes,
+        })
+    }
+
+    /// The cave made of the tile repeated `factor` times in both directions, with risks up to 9
+    pub fn from_tile(tile: &Cave, factor: usize) -> Result<Cave, Error> {
+        let tiling = Tiling::new(factor, Tiling::default().max_risk)?;
+        Ok(tile.tiled(tiling))
     }
 
-    fn from_tile(tile: &Cave, factor: usize) -> Cave {
-        let width = (tile.risk.len() as f64).sqrt() as usize;
-        let mut risk: Vec<usize> = Vec::with_capacity(tile.risk.len() * factor * factor);
-        for f_y in 0..factor {
-            for y in 0..width {
-                for f_x in 0..factor {
-                    for x in 0..width {
-                        let new_risk = tile.risk[y * width + x] + f_y + f_x;
-                        let new_risk = ((new_risk - 1) % 9) + 1;
-                        risk.push(new_risk);
-                    }
+    /// The cave with its first tile repeated. The tiles are not stored, their risks are worked
+    /// out when needed, so even huge caves fit into memory.
+    pub fn tiled(&self, tiling: Tiling) -> Cave {
+        Cave {
+            tiling,
+            ..self.clone()
+        }
+    }
+
+    /// Every risk of the tiled cave
+    fn get_risks(&self) -> Grid<usize> {
+        let risks = (0..self.get_height())
+            .flat_map(|y| (0..self.get_width()).map(move |x| (x, y)))
+            .map(|position| self.get_risk(position))
+            .collect();
+        Grid::new(self.get_width(), risks).expect("the tiles fill whole rows")
+    }
+
+    /// The risk map with the positions of the route in bold red, for a terminal. Positions
+    /// outside of the cave, e.g. of a route in a tiled cave, are left out.
+    pub fn render_route(&self, route: &Route) -> String {
+        let mut on_route = Grid::filled(self.get_width(), self.get_height(), false)
+            .expect("a parsed cave is at least 1 position wide");
+        for &position in route.positions.iter().filter(|&&p| self.contains(p)) {
+            on_route[position] = true;
+        }
+        let mut rendered = String::new();
+        for (y, row) in self.get_risks().rows().enumerate() {
+            for (x, risk) in row.iter().enumerate() {
+                match on_route[(x, y)] {
+                    true => rendered += &format!("\x1b[1;31m{}\x1b[0m", risk),
+                    false => rendered += &risk.to_string(),
                 }
             }
+            rendered.push('\n');
         }
-        let neighbours = HashMap::with_capacity(risk.len());
-        let mut cave = Cave { risk, neighbours };
-        cave.set_neighbours();
-        cave
+        rendered
     }
 }
 
 impl FromStr for Cave {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let risk: Vec<usize> = s
-            .trim()
-            .chars()
-            .filter_map(|c| if c.is_numeric() { c.to_digit(10) } else { None })
-            .map(|v| v as usize)
-            .collect();
-        let neighbours = HashMap::with_capacity(risk.len());
-        let mut cave = Cave { risk, neighbours };
-        cave.set_neighbours();
-        Ok(cave)
+        let risk = Grid::parse(s, |c| parse_digit(c).map(usize::from))?;
+        Ok(Cave {
+            risk,
+            tiling: Tiling::default(),
+            moves: Neighbourhood::Four.get_offsets().to_vec(),
+            move_cost: None,
+        })
+    }
+}
+
+impl Display for Cave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_risks())
+    }
+}
+
+/// The risk from dark to light, with the path of the lowest total risk in red
+impl Draw for Cave {
+    fn draw(&self) -> Image {
+        let route = self
+            .find_route((0, 0), self.get_end())
+            .map(|route| route.positions)
+            .unwrap_or_default();
+        let mut cells = self.get_risks().map(|&risk| Some(risk));
+        for position in route {
+            cells[position] = None;
+        }
+        let max_risk = self.tiling.max_risk;
+        Image::from_grid(cells.get_width(), cells.get_cells(), |cell| match cell {
+            Some(risk) => gradient([16, 16, 16], [160, 200, 160], *risk, max_risk),
+            None => [220, 20, 60],
+        })
     }
 }
 
@@ -127,9 +316,28 @@ pub mod tests {
         assert!(cave.is_ok());
         let cave = cave.unwrap();
         assert_eq!(100, cave.risk.len());
-        assert_eq!(2, cave.neighbours[&0].len());
-        assert_eq!(3, cave.neighbours[&1].len());
-        assert_eq!(4, cave.neighbours[&11].len());
+        assert_eq!(
+            vec![((1, 0), 1), ((0, 1), 1)],
+            cave.get_neighbours((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(3, cave.get_neighbours((1, 0)).count());
+        assert_eq!(4, cave.get_neighbours((1, 1)).count());
+    }
+
+    #[test]
+    fn it_rejects_invalid_caves() {
+        assert_eq!(Some(2), "12\n3".parse::<Cave>().err().and_then(|e| e.line));
+        assert_eq!(Some(2), "12\n3x".parse::<Cave>().err().and_then(|e| e.column));
+    }
+
+    #[test]
+    fn it_accepts_rectangular_caves() {
+        let cave: Cave = "123\n456".parse().unwrap();
+        assert_eq!((2, 1), cave.get_end());
+        assert_eq!(Some(2 + 3 + 6), cave.find_path((0, 0), cave.get_end()));
+        let cave = Cave::from_tile(&cave, 2).unwrap();
+        assert_eq!((5, 3), cave.get_end());
+        assert_eq!("123234\n456567\n234345\n567678\n", cave.to_string());
     }
 
     #[test]
@@ -145,20 +353,112 @@ pub mod tests {
         1293138521
         2311944581";
         let cave: Cave = input.parse().unwrap();
-        assert_eq!(40, cave.find_path(0, 99));
+        assert_eq!(Some(40), cave.find_path((0, 0), (9, 9)));
+        let route = cave.find_route((0, 0), cave.get_end()).unwrap();
+        assert_eq!(40, route.risk);
+        let positions = &route.positions;
+        assert_eq!((Some(&(0, 0)), Some(&(9, 9))), (positions.first(), positions.last()));
+        assert_eq!(40, positions[1..].iter().map(|&p| cave.risk[p]).sum::<usize>());
+        assert_eq!([220, 20, 60], cave.draw().pixels[99]);
+        // Anywhere to anywhere, also backwards
+        assert_eq!(Some(0), cave.find_path((3, 4), (3, 4)));
+        assert_eq!(Some(1 + 1), cave.find_path((2, 0), (0, 0)));
+        assert_eq!(None, cave.find_route((0, 0), (10, 9)));
+    }
+
+    #[test]
+    fn it_follows_movement_rules() {
+        let cave: Cave = "199\n919\n991".parse().unwrap();
+        assert_eq!(Some(1 + 9 + 9 + 1), cave.find_path((0, 0), (2, 2)));
+        let cave = cave.with_neighbourhood(Neighbourhood::Eight);
+        assert_eq!(Some(2), cave.find_path((0, 0), (2, 2)));
+        // Diagonal moves cost 10 more, going around the 9s is cheaper again
+        let cave = cave.with_move_cost(|(dx, dy)| if dx != 0 && dy != 0 { 10 } else { 0 });
+        assert_eq!(Some(1 + 9 + 9 + 1), cave.find_path((0, 0), (2, 2)));
+        let knight_moves = vec![
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ];
+        let cave = cave.with_moves(knight_moves.clone()).unwrap();
+        // A knight never reaches the middle of a 3x3 board
+        assert_eq!(None, cave.find_path((0, 0), (1, 1)));
+        // The move cost is kept, and knight moves are never straight
+        assert_eq!(Some(9 + 10), cave.find_path((0, 0), (2, 1)));
+        let cave: Cave = "1111\n1111\n1111".parse().unwrap();
+        let cave = cave.with_moves(knight_moves.clone()).unwrap();
+        let route = cave.find_route((0, 0), (3, 2)).unwrap();
+        assert_eq!(route.positions.len() - 1, route.risk);
+        for step in route.positions.windows(2) {
+            let dx = step[1].0 as isize - step[0].0 as isize;
+            let dy = step[1].1 as isize - step[0].1 as isize;
+            assert!(knight_moves.contains(&(dx, dy)));
+        }
+    }
+
+    #[test]
+    fn it_rejects_moves_that_go_nowhere() {
+        let cave: Cave = "12
+34".parse().unwrap();
+        assert!(cave.clone().with_moves(vec![]).is_err());
+        assert!(cave.clone().with_moves(vec![(0, 0)]).is_err());
+        assert!(cave.clone().with_moves(vec![(1, 0), (0, 0)]).is_err());
+        assert!(cave.with_moves(vec![(1, 1)]).is_ok());
+    }
+
+    #[test]
+    fn it_renders_route() {
+        let cave: Cave = "19\n11".parse().unwrap();
+        let route = cave.find_route((0, 0), cave.get_end()).unwrap();
+        assert_eq!(vec![(0, 0), (0, 1), (1, 1)], route.positions);
+        assert_eq!(
+            "\x1b[1;31m1\x1b[0m9\n\x1b[1;31m1\x1b[0m\x1b[1;31m1\x1b[0m\n",
+            cave.render_route(&route)
+        );
+        assert_eq!("19\n11\n", cave.to_string());
+        // Only the part of the route in the cave
+        let tiled = Cave::from_tile(&cave, 2).unwrap();
+        let route = tiled.find_route((0, 0), tiled.get_end()).unwrap();
+        assert_eq!(
+            "\x1b[1;31m1\x1b[0m9\n\x1b[1;31m1\x1b[0m\x1b[1;31m1\x1b[0m\n",
+            cave.render_route(&route)
+        );
     }
 
     #[test]
     fn it_tiles() {
-        let cave = Cave {
-            risk: vec![8],
-            neighbours: HashMap::new(),
-        };
-        let cave2 = Cave::from_tile(&cave, 5);
+        let cave: Cave = "8".parse().unwrap();
+        let cave2 = Cave::from_tile(&cave, 5).unwrap();
         let expected = vec![
             8, 9, 1, 2, 3, 9, 1, 2, 3, 4, 1, 2, 3, 4, 5, 2, 3, 4, 5, 6, 3, 4, 5, 6, 7,
         ];
-        assert_eq!(expected, cave2.risk);
+        assert_eq!(expected, cave2.get_risks().get_cells());
+        let cave3 = cave.tiled(Tiling::new(3, 10).unwrap());
+        assert_eq!(vec![8, 9, 10, 9, 10, 1, 10, 1, 2], cave3.get_risks().get_cells());
+        let cave1 = cave.tiled(Tiling::new(2, 1).unwrap());
+        assert_eq!(vec![1, 1, 1, 1], cave1.get_risks().get_cells());
+    }
+
+    #[test]
+    fn it_finds_path_through_wrapped_risks() {
+        // With a maximum risk of 4 the 5s wrap around to 1, below the lowest risk of the tile
+        let cave: Cave = "585\n555".parse().unwrap();
+        let cave = cave.tiled(Tiling::new(1, 4).unwrap());
+        assert_eq!("141\n111\n", cave.to_string());
+        // Around the 4 is cheaper than through it
+        assert_eq!(Some(1 + 1 + 1 + 1), cave.find_path((0, 0), (2, 0)));
+    }
+
+    #[test]
+    fn it_rejects_empty_tilings() {
+        assert!(Tiling::new(0, 9).is_err());
+        assert!(Tiling::new(5, 0).is_err());
+        assert!(Cave::from_tile(&"8".parse().unwrap(), 0).is_err());
     }
 
     #[test]
@@ -174,7 +474,10 @@ pub mod tests {
         1293138521
         2311944581";
         let cave: Cave = input.parse().unwrap();
-        let cave = Cave::from_tile(&cave, 5);
-        assert_eq!(315, cave.find_path(0, cave.risk.len() - 1));
+        let cave = Cave::from_tile(&cave, 5).unwrap();
+        assert_eq!(Some(315), cave.find_path((0, 0), cave.get_end()));
+        let cave = cave.tiled(Tiling::new(50, 9).unwrap());
+        assert_eq!((499, 499), cave.get_end());
+        assert!(cave.find_path((0, 0), cave.get_end()).is_some());
     }
 }
@@ -1,14 +1,32 @@
-use std::str::FromStr;
+use std::{
+    fmt::Display,
+    io::{ErrorKind, Read},
+    str::FromStr,
+};
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let packet: Packet = input.parse().unwrap();
-    println!("Day 16 part 1: {}", packet.sum_of_versions());
-    println!("Day 16 part 2: {}", packet.get_value());
+use bitvec::prelude::*;
+use itertools::Itertools;
+
+use crate::{error::Error, solution::Solution};
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Packet;
+    type Answer1 = usize;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.sum_of_versions())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        input.get_value()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,89 +39,347 @@ pub enum Packet {
 pub struct Literal {
     version: u8,
     type_id: u8,
-    payload: usize,
+    payload: u128,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Operator {
     version: u8,
-    type_id: u8,
+    operator_type: OperatorType,
     sub_packets: Vec<Packet>,
 }
 
-impl Packet {
-    fn parse(data: &[u8], used_bits: &mut usize) -> Packet {
-        let version = Packet::use_bits(data, used_bits, 3);
-        let type_id = Packet::use_bits(data, used_bits, 3);
+/// What an operator calculates from the values of its sub-packets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperatorType {
+    Sum,
+    Product,
+    Min,
+    Max,
+    /// 1 if the first sub-packet is greater than the second, otherwise 0
+    Gt,
+    /// 1 if the first sub-packet is less than the second, otherwise 0
+    Lt,
+    /// 1 if both sub-packets are equal, otherwise 0
+    Eq,
+}
+
+/// How an operator tells where its sub-packets end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// Length type ID 0, the number of bits of all sub-packets in 15 bits
+    TotalLength,
+    /// Length type ID 1, the number of sub-packets in 11 bits
+    SubPacketCount,
+}
+
+/// What the disassembler lists for a packet, see [Packet::disassemble]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// The bit the packet starts at
+    pub offset: usize,
+    /// How many operators the packet is in
+    pub depth: usize,
+    pub version: u8,
+    pub type_id: u8,
+    pub content: Content,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Content {
+    Literal(u128),
+    /// The length in bits with [LengthType::TotalLength], or the number of sub-packets
+    Operator(OperatorType, LengthType, usize),
+}
+
+/// How deep operators may be nested in each other
+const MAX_DEPTH: usize = 256;
+
+impl OperatorType {
+    /// `None` for literals and unknown type IDs
+    pub fn from_type_id(type_id: u8) -> Option<OperatorType> {
         match type_id {
-            4 => {
-                let mut payload: usize = 0;
-                loop {
-                    let v = Packet::use_bits(data, used_bits, 5);
-                    let first_bit_set = (v & 0b10000) > 0;
-                    payload = (payload << 4) | (v & 0xf) as usize;
-                    if !first_bit_set {
-                        break;
-                    }
+            0 => Some(OperatorType::Sum),
+            1 => Some(OperatorType::Product),
+            2 => Some(OperatorType::Min),
+            3 => Some(OperatorType::Max),
+            5 => Some(OperatorType::Gt),
+            6 => Some(OperatorType::Lt),
+            7 => Some(OperatorType::Eq),
+            _ => None,
+        }
+    }
+
+    pub fn get_type_id(self) -> u8 {
+        match self {
+            OperatorType::Sum => 0,
+            OperatorType::Product => 1,
+            OperatorType::Min => 2,
+            OperatorType::Max => 3,
+            OperatorType::Gt => 5,
+            OperatorType::Lt => 6,
+            OperatorType::Eq => 7,
+        }
+    }
+
+    /// The name in listings and expressions
+    pub fn get_name(self) -> &'static str {
+        match self {
+            OperatorType::Sum => "sum",
+            OperatorType::Product => "product",
+            OperatorType::Min => "min",
+            OperatorType::Max => "max",
+            OperatorType::Gt => "gt",
+            OperatorType::Lt => "lt",
+            OperatorType::Eq => "eq",
+        }
+    }
+
+    /// The symbol in S-expressions
+    pub fn get_symbol(self) -> &'static str {
+        match self {
+            OperatorType::Sum => "+",
+            OperatorType::Product => "*",
+            OperatorType::Min => "min",
+            OperatorType::Max => "max",
+            OperatorType::Gt => ">",
+            OperatorType::Lt => "<",
+            OperatorType::Eq => "=",
+        }
+    }
+
+    /// Whether the operator can be applied to that many sub-packets: comparisons take exactly 2,
+    /// all other operators at least 1
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            OperatorType::Gt | OperatorType::Lt | OperatorType::Eq => count == 2,
+            _ => count > 0,
+        }
+    }
+}
+
+impl Packet {
+    pub fn literal(version: u8, payload: u128) -> Packet {
+        Packet::Literal(Literal {
+            version,
+            type_id: 4,
+            payload,
+        })
+    }
+
+    /// Fails if the operator cannot be applied to that many sub-packets
+    pub fn operator(
+        version: u8,
+        operator_type: OperatorType,
+        sub_packets: Vec<Packet>,
+    ) -> Result<Packet, Error> {
+        if !operator_type.accepts(sub_packets.len()) {
+            return Err(Error::invalid(format!(
+                "{} cannot be applied to {} sub-packets",
+                operator_type.get_name(),
+                sub_packets.len()
+            )));
+        }
+        Ok(Packet::Operator(Operator {
+            version,
+            operator_type,
+            sub_packets,
+        }))
+    }
+
+    /// Reads one packet and its sub-packets, from any source of bytes
+    pub fn read_from<R: Read>(reader: &mut BitReader<R>) -> Result<Packet, Error> {
+        Packet::read(reader, 0, None)
+    }
+
+    /// Like [Packet::read_from], but also lists the header of every packet in the order they
+    /// were read
+    pub fn disassemble<R: Read>(reader: &mut BitReader<R>) -> Result<(Packet, Vec<Header>), Error> {
+        let mut headers = Vec::new();
+        let packet = Packet::read(reader, 0, Some(&mut headers))?;
+        Ok((packet, headers))
+    }
+
+    fn read<R: Read>(
+        reader: &mut BitReader<R>,
+        depth: usize,
+        mut headers: Option<&mut Vec<Header>>,
+    ) -> Result<Packet, Error> {
+        // Corrupt transmissions could nest packets until the stack overflows
+        if depth > MAX_DEPTH {
+            return Err(Error::invalid(format!(
+                "packets are nested more than {} levels deep at bit {}",
+                MAX_DEPTH,
+                reader.get_offset()
+            )));
+        }
+        let offset = reader.get_offset();
+        let version = reader.read(3)? as u8;
+        let type_id = reader.read(3)? as u8;
+        let header = |content| Header {
+            offset,
+            depth,
+            version,
+            type_id,
+            content,
+        };
+        if type_id == 4 {
+            let mut payload: u128 = 0;
+            loop {
+                let group = reader.read(5)?;
+                if payload.leading_zeros() < 4 {
+                    return Err(Error::unsupported(format!(
+                        "the literal at bit {} does not fit into 128 bits",
+                        offset
+                    )));
+                }
+                payload = (payload << 4) | (group & 0xf) as u128;
+                if group & 0b10000 == 0 {
+                    break;
                 }
-                Packet::Literal(Literal {
-                    version,
-                    type_id,
-                    payload,
-                })
             }
-            _ => {
-                let length_type_id = Packet::use_bits(data, used_bits, 1);
-                if length_type_id == 0 {
-                    let total_length_a = Packet::use_bits(data, used_bits, 7);
-                    let total_length_b = Packet::use_bits(data, used_bits, 8);
-                    let total_length: u16 =
-                        ((total_length_a as u16) << 7) | (total_length_b as u16);
-                    let mut sub_packets: Vec<Packet> = Vec::new();
-                    let start_bit = *used_bits;
-                    while *used_bits < start_bit + (total_length as usize) {
-                        sub_packets.push(Packet::parse(data, used_bits));
+            if let Some(headers) = headers {
+                headers.push(header(Content::Literal(payload)));
+            }
+            return Ok(Packet::Literal(Literal {
+                version,
+                type_id,
+                payload,
+            }));
+        }
+        let operator_type = OperatorType::from_type_id(type_id).ok_or_else(|| {
+            Error::invalid(format!(
+                "the packet at bit {} has the unknown type ID {}",
+                offset, type_id
+            ))
+        })?;
+        let mut sub_packets: Vec<Packet> = Vec::new();
+        if reader.read(1)? == 0 {
+            let total_length = reader.read(15)? as usize;
+            if let Some(headers) = headers.as_deref_mut() {
+                headers.push(header(Content::Operator(
+                    operator_type,
+                    LengthType::TotalLength,
+                    total_length,
+                )));
+            }
+            let end = reader.get_offset() + total_length;
+            while reader.get_offset() < end {
+                sub_packets.push(Packet::read(reader, depth + 1, headers.as_deref_mut())?);
+            }
+            if reader.get_offset() != end {
+                return Err(Error::invalid(format!(
+                    "the sub-packets end at bit {}, but their total length ends at bit {}",
+                    reader.get_offset(),
+                    end
+                )));
+            }
+        } else {
+            let number_subpackets = reader.read(11)?;
+            if let Some(headers) = headers.as_deref_mut() {
+                let length = number_subpackets as usize;
+                headers.push(header(Content::Operator(
+                    operator_type,
+                    LengthType::SubPacketCount,
+                    length,
+                )));
+            }
+            for _ in 0..number_subpackets {
+                sub_packets.push(Packet::read(reader, depth + 1, headers.as_deref_mut())?);
+            }
+        }
+        if !operator_type.accepts(sub_packets.len()) {
+            return Err(Error::invalid(format!(
+                "{} at bit {} cannot be applied to {} sub-packets",
+                operator_type.get_name(),
+                offset,
+                sub_packets.len()
+            )));
+        }
+        Ok(Packet::Operator(Operator {
+            version,
+            operator_type,
+            sub_packets,
+        }))
+    }
+
+    /// The packet as hexadecimal, like the puzzle input
+    pub fn to_hex(&self, length_type: LengthType) -> Result<String, Error> {
+        Ok(hex::encode_upper(self.encode(length_type)?.into_vec()))
+    }
+
+    /// The bits of the packet, padded with zeros to whole bytes. Every operator uses the
+    /// length type.
+    pub fn encode(&self, length_type: LengthType) -> Result<BitVec<Msb0, u8>, Error> {
+        let mut bits = BitVec::new();
+        self.encode_into(&mut bits, length_type)?;
+        bits.resize(bits.len().div_ceil(8) * 8, false);
+        Ok(bits)
+    }
+
+    fn encode_into(
+        &self,
+        bits: &mut BitVec<Msb0, u8>,
+        length_type: LengthType,
+    ) -> Result<(), Error> {
+        let (version, type_id) = match self {
+            Packet::Literal(literal) => (literal.version, literal.type_id),
+            Packet::Operator(operator) => (operator.version, operator.operator_type.get_type_id()),
+        };
+        Packet::push_bits(bits, version as usize, 3, "version")?;
+        Packet::push_bits(bits, type_id as usize, 3, "type ID")?;
+        match self {
+            Packet::Literal(Literal { payload, .. }) => {
+                let groups = (u128::BITS - payload.leading_zeros()).div_ceil(4).max(1);
+                for group in (0..groups).rev() {
+                    bits.push(group > 0);
+                    let value = (payload >> (4 * group)) & 0xf;
+                    Packet::push_bits(bits, value as usize, 4, "group")?;
+                }
+            }
+            Packet::Operator(Operator { sub_packets, .. }) => match length_type {
+                LengthType::TotalLength => {
+                    bits.push(false);
+                    let start = bits.len();
+                    // The length is only known afterwards, so it is filled in then
+                    Packet::push_bits(bits, 0, 15, "total length")?;
+                    for sub_packet in sub_packets {
+                        sub_packet.encode_into(bits, length_type)?;
                     }
-                    Packet::Operator(Operator {
-                        version,
-                        type_id,
-                        sub_packets,
-                    })
-                } else {
-                    let number_subpackets_a = Packet::use_bits(data, used_bits, 3);
-                    let number_subpackets_b = Packet::use_bits(data, used_bits, 8);
-                    let number_subpackets: u16 =
-                        ((number_subpackets_a as u16) << 7) | (number_subpackets_b as u16);
-                    let mut sub_packets: Vec<Packet> =
-                        Vec::with_capacity(number_subpackets as usize);
-                    for _ in 0..number_subpackets {
-                        sub_packets.push(Packet::parse(data, used_bits));
+                    let length = bits.len() - start - 15;
+                    let mut total_length = BitVec::<Msb0, u8>::new();
+                    Packet::push_bits(&mut total_length, length, 15, "total length")?;
+                    bits[start..start + 15].copy_from_bitslice(&total_length);
+                }
+                LengthType::SubPacketCount => {
+                    bits.push(true);
+                    Packet::push_bits(bits, sub_packets.len(), 11, "number of sub-packets")?;
+                    for sub_packet in sub_packets {
+                        sub_packet.encode_into(bits, length_type)?;
                     }
-                    Packet::Operator(Operator {
-                        version,
-                        type_id,
-                        sub_packets,
-                    })
                 }
-            }
+            },
         }
+        Ok(())
     }
 
-    fn use_bits(data: &[u8], used_bits: &mut usize, quantity: usize) -> u8 {
-        if quantity > 8 {
-            unimplemented!()
+    /// Appends the lowest bits of the value, highest first
+    fn push_bits(
+        bits: &mut BitVec<Msb0, u8>,
+        value: usize,
+        quantity: usize,
+        name: &str,
+    ) -> Result<(), Error> {
+        if quantity < usize::BITS as usize && value >> quantity != 0 {
+            return Err(Error::invalid(format!(
+                "the {} {} does not fit into {} bits",
+                name, value, quantity
+            )));
         }
-        let v = if 8 - (*used_bits % 8) >= quantity {
-            (data[*used_bits / 8] << (*used_bits % 8)) >> (8 - quantity)
-        } else {
-            // first get the remaining bits from the first byte and shift them so that they are in the correct position
-            let buf = (data[*used_bits / 8] << (*used_bits % 8)) >> (8 - quantity);
-            let used_bits = *used_bits + quantity;
-            // now OR the remaining bits to it
-            buf | (data[used_bits / 8] >> (8 - (used_bits % 8)))
-        };
-        *used_bits += quantity;
-        v
+        for bit in (0..quantity).rev() {
+            bits.push((value >> bit) & 1 == 1);
+        }
+        Ok(())
     }
 
     pub fn sum_of_versions(&self) -> usize {
@@ -116,57 +392,247 @@ impl Packet {
         }
     }
 
-    pub fn get_value(&self) -> usize {
+    /// Evaluates the packet in 128 bits. Fails if an operator does not fit its sub-packets, or
+    /// its result is too large, naming the packet.
+    pub fn get_value(&self) -> Result<u128, Error> {
+        self.evaluate(&mut Vec::new())
+    }
+
+    /// `path` holds the indices of the sub-packets that lead to this packet
+    fn evaluate(&self, path: &mut Vec<usize>) -> Result<u128, Error> {
+        let (operator_type, sub_packets) = match self {
+            Packet::Literal(literal) => return Ok(literal.payload),
+            Packet::Operator(operator) => (operator.operator_type, &operator.sub_packets),
+        };
+        let mut values = Vec::with_capacity(sub_packets.len());
+        for (i, sub_packet) in sub_packets.iter().enumerate() {
+            path.push(i);
+            values.push(sub_packet.evaluate(path)?);
+            path.pop();
+        }
+        let too_large = |result: &str| {
+            Error::unsolvable(format!(
+                "the {} of {} does not fit into 128 bits",
+                result,
+                Packet::get_location(path)
+            ))
+        };
+        // Packets are checked when they are made, so the arity only ever fails to match if
+        // those checks miss a case
+        match (operator_type, &values[..]) {
+            (OperatorType::Sum, [_, ..]) => values
+                .iter()
+                .try_fold(0u128, |sum, value| sum.checked_add(*value))
+                .ok_or_else(|| too_large("sum")),
+            (OperatorType::Product, [_, ..]) => values
+                .iter()
+                .try_fold(1u128, |product, value| product.checked_mul(*value))
+                .ok_or_else(|| too_large("product")),
+            (OperatorType::Min, [first, rest @ ..]) => {
+                Ok(rest.iter().fold(*first, |min, &value| min.min(value)))
+            }
+            (OperatorType::Max, [first, rest @ ..]) => {
+                Ok(rest.iter().fold(*first, |max, &value| max.max(value)))
+            }
+            (OperatorType::Gt, [a, b]) => Ok((a > b) as u128),
+            (OperatorType::Lt, [a, b]) => Ok((a < b) as u128),
+            (OperatorType::Eq, [a, b]) => Ok((a == b) as u128),
+            _ => Err(Error::invalid(format!(
+                "{} of {} cannot be applied to {} sub-packets",
+                operator_type.get_name(),
+                Packet::get_location(path),
+                values.len()
+            ))),
+        }
+    }
+
+    /// Where the packet is in the tree, by the indices of the sub-packets that lead to it
+    fn get_location(path: &[usize]) -> String {
+        match path {
+            [] => "the outermost packet".to_owned(),
+            _ => format!("sub-packet {}", path.iter().join(".")),
+        }
+    }
+}
+
+impl Packet {
+    /// The packet as an S-expression, e.g. `(max 3 (+ 1 (* 2 5)))`
+    pub fn to_s_expression(&self) -> String {
         match self {
-            Packet::Literal(literal) => literal.payload,
-            Packet::Operator(Operator {
-                version: _,
-                type_id,
-                sub_packets,
-            }) => match type_id {
-                0 => sub_packets.iter().map(|sub| sub.get_value()).sum(),
-                1 => sub_packets.iter().map(|sub| sub.get_value()).product(),
-                2 => sub_packets.iter().map(|sub| sub.get_value()).min().unwrap(),
-                3 => sub_packets.iter().map(|sub| sub.get_value()).max().unwrap(),
-                5 => {
-                    if sub_packets[0].get_value() > sub_packets[1].get_value() {
-                        1
-                    } else {
-                        0
-                    }
+            Packet::Literal(literal) => literal.payload.to_string(),
+            Packet::Operator(operator) => {
+                let symbol = operator.operator_type.get_symbol().to_owned();
+                let operands = operator.sub_packets.iter().map(|p| p.to_s_expression());
+                format!("({})", [symbol].into_iter().chain(operands).join(" "))
+            }
+        }
+    }
+
+    /// `nested` is set for the operands of comparisons, which then need parentheses themselves
+    fn fmt_expression(&self, f: &mut std::fmt::Formatter<'_>, nested: bool) -> std::fmt::Result {
+        let operator = match self {
+            Packet::Literal(literal) => return write!(f, "{}", literal.payload),
+            Packet::Operator(operator) => operator,
+        };
+        let infix = match operator.operator_type {
+            OperatorType::Gt => Some(">"),
+            OperatorType::Lt => Some("<"),
+            OperatorType::Eq => Some("=="),
+            _ => None,
+        };
+        match (infix, &operator.sub_packets[..]) {
+            (Some(infix), [a, b]) => {
+                if nested {
+                    write!(f, "(")?;
                 }
-                6 => {
-                    if sub_packets[0].get_value() < sub_packets[1].get_value() {
-                        1
-                    } else {
-                        0
-                    }
+                a.fmt_expression(f, true)?;
+                write!(f, " {} ", infix)?;
+                b.fmt_expression(f, true)?;
+                if nested {
+                    write!(f, ")")?;
                 }
-                7 => {
-                    if sub_packets[0].get_value() == sub_packets[1].get_value() {
-                        1
-                    } else {
-                        0
+            }
+            _ => {
+                write!(f, "{}(", operator.operator_type.get_name())?;
+                for (i, sub_packet) in operator.sub_packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
                     }
+                    sub_packet.fmt_expression(f, false)?;
                 }
-                _ => panic!(),
-            },
+                write!(f, ")")?;
+            }
         }
+        Ok(())
+    }
+}
+
+/// The packet as an expression, e.g. `max(3, sum(1, product(2, 5)))` or `1 + 2 == 3`
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_expression(f, false)
     }
 }
 
+/// One line of a listing: the offset, the packet indented by its depth, its version, type ID and
+/// literal value, or operator name and length
+impl Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>6}  {}v{} t{} ",
+            self.offset,
+            "  ".repeat(self.depth),
+            self.version,
+            self.type_id
+        )?;
+        match self.content {
+            Content::Literal(value) => write!(f, "literal {}", value),
+            Content::Operator(operator_type, length_type, length) => {
+                write!(f, "{}", operator_type.get_name())?;
+                match length_type {
+                    LengthType::TotalLength => write!(f, ", {} bits of sub-packets", length),
+                    LengthType::SubPacketCount => write!(f, ", {} sub-packets", length),
+                }
+            }
+        }
+    }
+}
+
+/// Lists the header of every packet in a hexadecimal transmission, see [Header]
+pub fn disassemble(message: &str) -> Result<(Packet, Vec<Header>), Error> {
+    Packet::disassemble(&mut BitReader::new(decode_hex(message)?.as_slice()))
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(s.trim()).map_err(|e| Error::invalid(format!("cannot decode hexadecimal: {}", e)))
+}
+
 impl FromStr for Packet {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data = hex::decode(s.trim()).map_err(|_| "cannot parse input")?;
-        Ok(Packet::parse(data.as_slice(), &mut 0))
+        Packet::read_from(&mut BitReader::new(decode_hex(s)?.as_slice()))
+    }
+}
+
+/// Reads a transmission bit by bit, starting with the highest bit of every byte
+pub struct BitReader<R> {
+    source: R,
+    /// The bits of the current byte that were not read yet, moved to the top
+    byte: u8,
+    /// How many bits of the current byte are left
+    left: u32,
+    offset: usize,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(source: R) -> BitReader<R> {
+        BitReader {
+            source,
+            byte: 0,
+            left: 0,
+            offset: 0,
+        }
+    }
+
+    /// How many bits were read so far
+    pub fn get_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Reads up to 64 bits, the first one becomes the highest bit of the number.
+    pub fn read(&mut self, quantity: u32) -> Result<u64, Error> {
+        if quantity > u64::BITS {
+            return Err(Error::unsupported(format!(
+                "cannot read {} bits at once, at most {}",
+                quantity,
+                u64::BITS
+            )));
+        }
+        let mut value: u64 = 0;
+        let mut needed = quantity;
+        while needed > 0 {
+            if self.left == 0 {
+                self.byte = self.read_byte(needed)?;
+                self.left = 8;
+            }
+            let taken = needed.min(self.left);
+            value = (value << taken) | (self.byte >> (8 - taken)) as u64;
+            self.byte = self.byte.checked_shl(taken).unwrap_or(0);
+            self.left -= taken;
+            needed -= taken;
+            self.offset += taken as usize;
+        }
+        Ok(value)
+    }
+
+    fn read_byte(&mut self, needed: u32) -> Result<u8, Error> {
+        let mut byte = [0];
+        match self.source.read_exact(&mut byte) {
+            Ok(()) => Ok(byte[0]),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Err(Error::invalid(format!(
+                "the transmission ends after {} bits, but {} more are needed",
+                self.offset, needed
+            ))),
+            Err(e) => Err(Error::io(format!(
+                "cannot read the transmission after {} bits: {}",
+                self.offset, e
+            ))),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Cause;
+    use std::io;
+
+    fn operator(operator_type: OperatorType, sub_packets: Vec<Packet>) -> Packet {
+        Packet::operator(0, operator_type, sub_packets).unwrap()
+    }
+
     #[test]
     fn it_parses_literal() {
         let input = "D2FE28";
@@ -179,7 +645,7 @@ mod tests {
                 assert_eq!(4, literal.type_id);
                 assert_eq!(2021, literal.payload);
             }
-            Packet::Operator(_) => assert!(false),
+            Packet::Operator(_) => panic!("expected a literal"),
         };
     }
 
@@ -190,10 +656,10 @@ mod tests {
         assert!(packet.is_ok());
         let packet = packet.unwrap();
         match packet {
-            Packet::Literal(_) => assert!(false),
+            Packet::Literal(_) => panic!("expected an operator"),
             Packet::Operator(operator) => {
                 assert_eq!(1, operator.version);
-                assert_eq!(6, operator.type_id);
+                assert_eq!(OperatorType::Lt, operator.operator_type);
                 assert_eq!(2, operator.sub_packets.len());
             }
         };
@@ -217,18 +683,258 @@ mod tests {
 
     #[test]
     fn it_calculates_packet_values() {
-        assert_eq!(3, Packet::from_str("C200B40A82").unwrap().get_value());
-        assert_eq!(54, Packet::from_str("04005AC33890").unwrap().get_value());
-        assert_eq!(7, Packet::from_str("880086C3E88112").unwrap().get_value());
-        assert_eq!(9, Packet::from_str("CE00C43D881120").unwrap().get_value());
-        assert_eq!(1, Packet::from_str("D8005AC2A8F0").unwrap().get_value());
-        assert_eq!(0, Packet::from_str("F600BC2D8F").unwrap().get_value());
-        assert_eq!(0, Packet::from_str("9C005AC2F8F0").unwrap().get_value());
+        assert_eq!(Ok(3), Packet::from_str("C200B40A82").unwrap().get_value());
+        assert_eq!(Ok(54), Packet::from_str("04005AC33890").unwrap().get_value());
+        assert_eq!(Ok(7), Packet::from_str("880086C3E88112").unwrap().get_value());
+        assert_eq!(Ok(9), Packet::from_str("CE00C43D881120").unwrap().get_value());
+        assert_eq!(Ok(1), Packet::from_str("D8005AC2A8F0").unwrap().get_value());
+        assert_eq!(Ok(0), Packet::from_str("F600BC2D8F").unwrap().get_value());
+        assert_eq!(Ok(0), Packet::from_str("9C005AC2F8F0").unwrap().get_value());
         assert_eq!(
-            1,
+            Ok(1),
             Packet::from_str("9C0141080250320F1802104A08")
                 .unwrap()
                 .get_value()
         );
     }
+
+    #[test]
+    fn it_encodes_examples() {
+        let packet: Packet = "D2FE28".parse().unwrap();
+        assert_eq!(Ok("D2FE28".to_owned()), packet.to_hex(LengthType::TotalLength));
+        let packet: Packet = "38006F45291200".parse().unwrap();
+        assert_eq!(
+            Ok("38006F45291200".to_owned()),
+            packet.to_hex(LengthType::TotalLength)
+        );
+        let packet: Packet = "EE00D40C823060".parse().unwrap();
+        assert_eq!(
+            Ok("EE00D40C823060".to_owned()),
+            packet.to_hex(LengthType::SubPacketCount)
+        );
+        let literals = vec![
+            Packet::literal(2, 1),
+            Packet::literal(4, 2),
+            Packet::literal(1, 3),
+        ];
+        assert_eq!(Ok(packet), Packet::operator(7, OperatorType::Max, literals));
+    }
+
+    #[test]
+    fn it_round_trips() {
+        let examples = [
+            "8A004A801A8002F478",
+            "620080001611562C8802118E34",
+            "C0015000016115A2E0802F182340",
+            "A0016C880162017C3686B18A3D4780",
+            "9C0141080250320F1802104A08",
+            include_str!("../resources/day16.txt"),
+        ];
+        for example in examples {
+            let packet: Packet = example.parse().unwrap();
+            for length_type in [LengthType::TotalLength, LengthType::SubPacketCount] {
+                let hex = packet.to_hex(length_type).unwrap();
+                assert_eq!(Ok(&packet), hex.parse::<Packet>().as_ref(), "{}", example);
+            }
+        }
+        // More sub-packets and bits than fit into a byte, and literals of many groups
+        let literals = (0..300).map(|i| Packet::literal(i as u8 % 8, i * 0x1_0001)).collect();
+        let product = Packet::operator(1, OperatorType::Product, literals).unwrap();
+        let packet = Packet::operator(5, OperatorType::Sum, vec![product]).unwrap();
+        for length_type in [LengthType::TotalLength, LengthType::SubPacketCount] {
+            let hex = packet.to_hex(length_type).unwrap();
+            assert_eq!(Ok(&packet), hex.parse::<Packet>().as_ref());
+        }
+    }
+
+    #[test]
+    fn it_rejects_packets_that_cannot_be_encoded() {
+        assert!(Packet::literal(8, 1).to_hex(LengthType::TotalLength).is_err());
+        let literals: Vec<Packet> = (0..3000).map(|_| Packet::literal(0, 0)).collect();
+        let packet = operator(OperatorType::Sum, literals);
+        assert!(packet.to_hex(LengthType::SubPacketCount).is_err());
+        // 11 bits each, too many for a total length of 15 bits
+        assert!(packet.to_hex(LengthType::TotalLength).is_err());
+    }
+
+    #[test]
+    fn it_reads_bits() {
+        let data = [0b1011_0011, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0xff];
+        let mut reader = BitReader::new(&data[..]);
+        assert_eq!(Ok(0b101), reader.read(3));
+        assert_eq!(Ok(0), reader.read(0));
+        assert_eq!(Ok(0b1_0011_0001), reader.read(9));
+        assert_eq!(Ok(0x2345_6789_abcd_ef0f), reader.read(64));
+        assert_eq!(76, reader.get_offset());
+        assert!(reader.read(65).is_err());
+        let error = reader.read(5).unwrap_err();
+        assert_eq!(
+            Cause::Invalid("the transmission ends after 80 bits, but 1 more are needed".to_owned()),
+            error.cause
+        );
+    }
+
+    #[test]
+    fn it_reads_from_any_source() {
+        let data = hex::decode("8A004A801A8002F478").unwrap();
+        let packet = Packet::read_from(&mut BitReader::new(io::Cursor::new(data))).unwrap();
+        assert_eq!(16, packet.sum_of_versions());
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disconnected"))
+            }
+        }
+        let error = Packet::read_from(&mut BitReader::new(Broken)).unwrap_err();
+        assert!(matches!(error.cause, Cause::Io(_)));
+    }
+
+    #[test]
+    fn it_rejects_corrupt_transmissions() {
+        // The operator says its sub-packets take 10 bits, a literal takes 11
+        let literal = Packet::literal(1, 1).encode(LengthType::TotalLength).unwrap();
+        let mut bits: BitVec<Msb0, u8> = BitVec::new();
+        bits.extend([false, false, true, false, false, false, false]);
+        bits.extend((0..15).map(|i| i == 11 || i == 13));
+        bits.extend(&literal[..11]);
+        let error = Packet::read_from(&mut BitReader::new(bits.as_raw_slice())).unwrap_err();
+        assert!(error.to_string().contains("bit 33"), "{}", error);
+        let mut packet = Packet::literal(0, 0);
+        for _ in 0..=MAX_DEPTH {
+            packet = operator(OperatorType::Sum, vec![packet]);
+        }
+        let hex = packet.to_hex(LengthType::SubPacketCount).unwrap();
+        assert!(hex.parse::<Packet>().is_err());
+        // A huge number of sub-packets that are not there
+        assert!("EFFF00".parse::<Packet>().is_err());
+    }
+
+    #[test]
+    fn it_rejects_invalid_packets() {
+        assert!(Packet::from_str("D2FE2").is_err());
+        assert!(Packet::from_str("D2FE").is_err());
+        // a "less than" operator with a single literal
+        let message = "lt at bit 0 cannot be applied to 1 sub-packets";
+        assert_eq!(
+            Cause::Invalid(message.to_owned()),
+            Packet::from_str("1A004408").unwrap_err().cause
+        );
+    }
+
+    #[test]
+    fn it_validates_operators() {
+        for type_id in (0..8).filter(|&type_id| type_id != 4) {
+            let operator_type = OperatorType::from_type_id(type_id).unwrap();
+            assert_eq!(type_id, operator_type.get_type_id());
+        }
+        assert_eq!(None, OperatorType::from_type_id(4));
+        assert_eq!(None, OperatorType::from_type_id(8));
+        let literals = |count| (0..count).map(|i| Packet::literal(0, i)).collect();
+        for operator_type in [OperatorType::Gt, OperatorType::Lt, OperatorType::Eq] {
+            assert!(Packet::operator(0, operator_type, literals(1)).is_err());
+            assert!(Packet::operator(0, operator_type, literals(3)).is_err());
+            assert!(Packet::operator(0, operator_type, literals(2)).is_ok());
+        }
+        for operator_type in [
+            OperatorType::Sum,
+            OperatorType::Product,
+            OperatorType::Min,
+            OperatorType::Max,
+        ] {
+            assert!(Packet::operator(0, operator_type, literals(0)).is_err());
+            assert!(Packet::operator(0, operator_type, literals(1)).is_ok());
+            assert!(Packet::operator(0, operator_type, literals(3)).is_ok());
+        }
+        // A sum without sub-packets
+        let mut bits: BitVec<Msb0, u8> = BitVec::new();
+        bits.extend([false; 7]);
+        bits.extend([false; 15]);
+        let error = Packet::read_from(&mut BitReader::new(bits.as_raw_slice())).unwrap_err();
+        assert!(error.to_string().contains("sum at bit 0"), "{}", error);
+    }
+
+    #[test]
+    fn it_evaluates_large_values() {
+        let large = Packet::literal(0, 1 << 100);
+        let hex = large.to_hex(LengthType::TotalLength).unwrap();
+        assert_eq!(Ok(1 << 100), hex.parse::<Packet>().unwrap().get_value());
+        let product = operator(
+            OperatorType::Product,
+            vec![large, Packet::literal(0, 1 << 27)],
+        );
+        assert_eq!(Ok(1 << 127), product.get_value());
+        let max = Packet::literal(0, u128::MAX);
+        let sum = operator(OperatorType::Sum, vec![max.clone(), Packet::literal(0, 0)]);
+        assert_eq!(Ok(u128::MAX), sum.get_value());
+        let sum = operator(OperatorType::Sum, vec![max, Packet::literal(0, 1)]);
+        assert!(sum.get_value().is_err());
+        // The maximum of the minimum of 1 and a product that is too large
+        let too_large = operator(OperatorType::Product, vec![product, Packet::literal(0, 2)]);
+        let minimum = operator(OperatorType::Min, vec![Packet::literal(0, 1), too_large]);
+        let packet = operator(OperatorType::Max, vec![minimum]);
+        let message = "the product of sub-packet 0.1 does not fit into 128 bits";
+        assert_eq!(
+            Cause::Unsolvable(message.to_owned()),
+            packet.get_value().unwrap_err().cause
+        );
+    }
+
+    #[test]
+    fn it_disassembles() {
+        let (packet, headers) = disassemble("38006F45291200").unwrap();
+        assert_eq!(Ok(1), packet.get_value());
+        let listing: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        assert_eq!(
+            vec![
+                "     0  v1 t6 lt, 27 bits of sub-packets",
+                "    22    v6 t4 literal 10",
+                "    33    v2 t4 literal 20",
+            ],
+            listing
+        );
+        let (_, headers) = disassemble("EE00D40C823060").unwrap();
+        assert_eq!(
+            Content::Operator(OperatorType::Max, LengthType::SubPacketCount, 3),
+            headers[0].content
+        );
+        let header = headers[3];
+        assert_eq!(
+            (40, 1, 1, Content::Literal(3)),
+            (header.offset, header.depth, header.version, header.content)
+        );
+        assert!(disassemble("D2FE2").is_err());
+        assert!(disassemble("D2FE").is_err());
+    }
+
+    #[test]
+    fn it_prints_expressions() {
+        let product = operator(
+            OperatorType::Product,
+            vec![Packet::literal(0, 2), Packet::literal(0, 5)],
+        );
+        let sum = operator(OperatorType::Sum, vec![Packet::literal(0, 1), product]);
+        let max = operator(OperatorType::Max, vec![Packet::literal(0, 3), sum.clone()]);
+        assert_eq!("max(3, sum(1, product(2, 5)))", max.to_string());
+        assert_eq!("(max 3 (+ 1 (* 2 5)))", max.to_s_expression());
+        // Comparisons of comparisons need parentheses
+        let less = operator(OperatorType::Lt, vec![sum, Packet::literal(0, 12)]);
+        let equal = operator(OperatorType::Eq, vec![less, Packet::literal(0, 1)]);
+        assert_eq!("(sum(1, product(2, 5)) < 12) == 1", equal.to_string());
+        assert_eq!("(= (< (+ 1 (* 2 5)) 12) 1)", equal.to_s_expression());
+    }
+
+    #[test]
+    fn it_rejects_literals_beyond_128_bits() {
+        let mut bits: BitVec<Msb0, u8> = BitVec::new();
+        bits.extend([false, false, false, true, false, false]);
+        // 33 groups of 4 bits
+        for group in 0..33 {
+            bits.extend([group < 32, true, true, true, true]);
+        }
+        let error = Packet::read_from(&mut BitReader::new(bits.as_raw_slice())).unwrap_err();
+        assert_eq!(
+            Cause::Unsupported("the literal at bit 0 does not fit into 128 bits".to_owned()),
+            error.cause
+        );
+    }
 }
@@ -1,58 +1,74 @@
 use std::cmp::Ordering;
 
-pub fn solve() {
-    let target = Rectangle(Point(143, -71), Point(177, -106));
-    let best_flightpath = get_best_flightpath(&target);
-    println!(
-        "Day 17 part 1: {}",
-        (best_flightpath.1.pow(2) + best_flightpath.1) / 2
-    );
-    println!("Day 17 part 2: {}", get_possible_flightpaths(&target).len());
-}
+use crate::{
+    error::Error,
+    geometry::{Rectangle, Vector2},
+    solution::Solution,
+};
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
-pub struct Point(isize, isize);
+pub struct Day17;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
-pub struct Rectangle(Point, Point);
+impl Solution for Day17 {
+    type Input = Rectangle;
+    type Answer1 = isize;
+    type Answer2 = usize;
 
-impl Rectangle {
-    fn contains(&self, point: &Point) -> bool {
-        self.get_min_x() <= point.0
-            && self.get_max_x() >= point.0
-            && self.get_min_y() <= point.1
-            && self.get_max_y() >= point.1
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_target_area(input)
     }
 
-    fn get_min_y(&self) -> isize {
-        self.0 .1.min(self.1 .1)
-    }
-    fn get_min_x(&self) -> isize {
-        self.0 .0.min(self.1 .0)
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let best_flightpath = get_best_flightpath(input)
+            .ok_or_else(|| Error::unsolvable("no initial velocity hits the target area"))?;
+        Ok((best_flightpath.y().pow(2) + best_flightpath.y()) / 2)
     }
-    fn get_max_y(&self) -> isize {
-        self.0 .1.max(self.1 .1)
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(get_possible_flightpaths(input).len())
     }
-    fn get_max_x(&self) -> isize {
-        self.0 .0.max(self.1 .0)
+}
+
+/// Parses the puzzle input, e.g. `target area: x=20..30, y=-10..-5`
+pub fn parse_target_area(s: &str) -> Result<Rectangle, Error> {
+    let (x, y) = s
+        .trim()
+        .strip_prefix("target area: ")
+        .ok_or_else(|| Error::invalid("missing 'target area: '"))?
+        .split_once(", ")
+        .ok_or_else(|| Error::invalid("cannot split into x and y range"))?;
+    let parse_range = |range: &str, axis: &str| -> Result<(isize, isize), Error> {
+        let (from, to) = range
+            .strip_prefix(axis)
+            .ok_or_else(|| Error::invalid(format!("expected range of {}", axis)))?
+            .split_once("..")
+            .ok_or_else(|| Error::invalid("cannot split range at '..'"))?;
+        Ok((from.parse()?, to.parse()?))
+    };
+    let (x1, x2) = parse_range(x, "x=")?;
+    let (y1, y2) = parse_range(y, "y=")?;
+    let target = Rectangle::new(Vector2::new(x1, y1), Vector2::new(x2, y2));
+    if target.min.x() <= 0 || target.max.y() >= 0 {
+        return Err(Error::unsupported(
+            "the target area must be to the right of and below the launcher",
+        ));
     }
+    Ok(target)
 }
 
-fn get_best_flightpath(target: &Rectangle) -> Point {
+pub fn get_best_flightpath(target: &Rectangle) -> Option<Vector2> {
     get_possible_flightpaths(target)
         .iter()
         .max_by(|a, b| {
-            if a.1.cmp(&b.1) == Ordering::Equal {
-                (-a.0).cmp(&-&b.0)
+            if a.y().cmp(&b.y()) == Ordering::Equal {
+                (-a.x()).cmp(&-b.x())
             } else {
-                a.1.cmp(&b.1)
+                a.y().cmp(&b.y())
             }
         })
-        .unwrap()
-        .clone()
+        .cloned()
 }
 
-fn get_possible_flightpaths(target: &Rectangle) -> Vec<Point> {
+pub fn get_possible_flightpaths(target: &Rectangle) -> Vec<Vector2> {
     // We need the x velocity to be at least high enough so that (1+2+..+vel_x) = min_x of the rectangle
     // We need the x velocity to be at most max_x of the rectangle
     // the former can be solved via a quadratic equation since the sum of integers is gauss.. sum = (n²+n)/2 = 0.5n² + 0.5n.
@@ -62,39 +78,34 @@ fn get_possible_flightpaths(target: &Rectangle) -> Vec<Point> {
     //
     // For example, for a rectangle with x=20..30:
     // -0.5 +- sqrt(0.25 + 2*20) = ~ 6, rounded up
-    let min_x = (-0.5 + (0.25 + 2.0 * target.get_min_x() as f64).sqrt().ceil()) as isize;
-    let max_x: isize = target.get_max_x();
+    let min_x = (-0.5 + (0.25 + 2.0 * target.min.x() as f64).sqrt().ceil()) as isize;
+    let max_x: isize = target.max.x();
 
     // For y, the lower bound is the lowest corner of the rectangle
     // The upper bound can be calculated since the y points of the flight path are symmetric as can be seen in the example.
     // This means that max vel_y is the distance from start to the lower end of the target area
-    let mut possible_velocities: Vec<Point> = Vec::new();
+    let mut possible_velocities: Vec<Vector2> = Vec::new();
     for x in min_x..=max_x {
-        for y in target.get_min_y()..(-target.get_min_y()) {
-            if can_hit(target, Point(x, y)) {
-                possible_velocities.push(Point(x, y));
+        for y in target.min.y()..(-target.min.y()) {
+            if can_hit(target, Vector2::new(x, y)) {
+                possible_velocities.push(Vector2::new(x, y));
             }
         }
     }
     possible_velocities
 }
 
-fn can_hit(target: &Rectangle, initial_velocity: Point) -> bool {
+pub fn can_hit(target: &Rectangle, initial_velocity: Vector2) -> bool {
     let mut velocity = initial_velocity;
-    let mut probe = Point(0, 0);
+    let mut probe = Vector2::ZERO;
     loop {
-        probe.0 += velocity.0;
-        probe.1 += velocity.1;
-        velocity.0 += match velocity.0.cmp(&0) {
-            Ordering::Less => 1,
-            Ordering::Equal => 0,
-            Ordering::Greater => -1,
-        };
-        velocity.1 -= 1;
+        probe += velocity;
+        // Drag pulls x towards 0, gravity pulls y down
+        velocity -= Vector2::new(velocity.x().signum(), 1);
         if target.contains(&probe) {
             return true;
         }
-        if probe.0 > target.get_max_x() || probe.1 < target.get_min_y() {
+        if probe.x() > target.max.x() || probe.y() < target.min.y() {
             return false;
         }
     }
@@ -104,19 +115,34 @@ fn can_hit(target: &Rectangle, initial_velocity: Point) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn it_parses_target_area() {
+        let target = parse_target_area("target area: x=20..30, y=-10..-5").unwrap();
+        assert_eq!(
+            Rectangle::new(Vector2::new(20, -10), Vector2::new(30, -5)),
+            target
+        );
+        assert_eq!(-10, target.min.y());
+        assert_eq!(30, target.max.x());
+        assert!(parse_target_area("target area: x=20..30").is_err());
+        assert!(parse_target_area("x=20..30, y=-10..-5").is_err());
+        assert!(parse_target_area("target area: x=20..a, y=-10..-5").is_err());
+        assert!(parse_target_area("target area: x=-30..-20, y=-10..-5").is_err());
+    }
+
     #[test]
     fn it_gets_possible_flightpaths() {
-        let target = Rectangle(Point(20, -5), Point(30, -10));
+        let target = Rectangle::new(Vector2::new(20, -5), Vector2::new(30, -10));
         let possible_velocities = get_possible_flightpaths(&target);
-        assert!(!possible_velocities.contains(&Point(17, -4)));
+        assert!(!possible_velocities.contains(&Vector2::new(17, -4)));
         assert_eq!(112, possible_velocities.len());
     }
 
     #[test]
     fn it_gets_best_flightpath() {
-        let target = Rectangle(Point(20, -5), Point(30, -10));
-        let best_flightpath = get_best_flightpath(&target);
-        assert_eq!(Point(6, 9), best_flightpath);
-        assert_eq!(45, (best_flightpath.1.pow(2) + best_flightpath.1) / 2);
+        let target = Rectangle::new(Vector2::new(20, -5), Vector2::new(30, -10));
+        let best_flightpath = get_best_flightpath(&target).unwrap();
+        assert_eq!(Vector2::new(6, 9), best_flightpath);
+        assert_eq!(45, (best_flightpath.y().pow(2) + best_flightpath.y()) / 2);
     }
 }
@@ -1,22 +1,38 @@
 use std::{
     fmt,
-    iter::Sum,
     ops::{Add, AddAssign},
     str::FromStr,
 };
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let pairs =  input.trim()
-    .lines()
-    .map(|line| line.trim().parse())
-    .collect::<Result<Vec<Pair>, _>>().unwrap();
-    println!("Day 18 part 1: {}", pairs.iter().cloned().sum::<Pair>().magnitude());
-    println!("Day 18 part 2: {}", Pair::get_largest_magnitude(&pairs));
+use crate::{
+    error::Error,
+    parse::{parse_digit, parse_lines},
+    solution::Solution,
+};
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<Pair>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_lines(input, |line| line.trim().parse())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        input
+            .iter()
+            .cloned()
+            .reduce(|sum, pair| sum + pair)
+            .map(|sum| sum.magnitude())
+            .ok_or_else(|| Error::unsolvable("there are no snailfish numbers to add"))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(Pair::get_largest_magnitude(input))
+    }
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -48,33 +64,29 @@ impl fmt::Debug for Pair {
 
 impl Element {
     fn explode(&mut self, level: u16) -> Option<(u16, u16)> {
-        if level == 4 {
-            match self {
-                Element::Number(_) => None,
-                Element::Pair(pair) => {
-                    let cloned = pair.clone();
-                    *self = Element::Number(0);
-                    match (cloned.left.as_ref(), cloned.right.as_ref()) {
-                        (Element::Number(l), Element::Number(r)) => Some((*l, *r)),
-                        _ => panic!(),
+        match self {
+            Element::Number(_) => None,
+            Element::Pair(pair) => {
+                // Pairs that are nested even deeper explode first
+                if level >= 4 {
+                    if let (Element::Number(l), Element::Number(r)) =
+                        (pair.left.as_ref(), pair.right.as_ref())
+                    {
+                        let result = (*l, *r);
+                        *self = Element::Number(0);
+                        return Some(result);
                     }
                 }
-            }
-        } else {
-            match self {
-                Element::Number(_) => None,
-                Element::Pair(pair) => {
-                    if let Some(result_left) = pair.left.explode(level + 1) {
-                        // handle the right value
-                        pair.right.handle_rightgoing_value(result_left.1);
-                        return Some((result_left.0, 0));
-                    }
-                    if let Some(result_right) = pair.right.explode(level + 1) {
-                        pair.left.handle_leftgoing_value(result_right.0);
-                        return Some((0, result_right.1));
-                    }
-                    None
+                if let Some(result_left) = pair.left.explode(level + 1) {
+                    // handle the right value
+                    pair.right.handle_rightgoing_value(result_left.1);
+                    return Some((result_left.0, 0));
+                }
+                if let Some(result_right) = pair.right.explode(level + 1) {
+                    pair.left.handle_leftgoing_value(result_right.0);
+                    return Some((0, result_right.1));
                 }
+                None
             }
         }
     }
@@ -135,24 +147,35 @@ impl Element {
         }
     }
 
-    fn parse(chars: &[char], index: &mut usize) -> Element {
+    fn parse(chars: &[char], index: &mut usize) -> Result<Element, Error> {
+        let column = *index + 1;
         *index += 1;
-        let left = if chars[*index - 1] == '[' {
-            Element::parse(chars, index)
-        } else {
-            Element::Number(chars[*index - 1].to_digit(10).unwrap() as u16)
-        };
-        *index += 2; // because of the comma
-        let right = if chars[*index - 1] == '[' {
-            Element::parse(chars, index)
-        } else {
-            Element::Number(chars[*index - 1].to_digit(10).unwrap() as u16)
-        };
-        *index += 1;
-        Element::Pair(Pair {
-            left: Box::new(left),
-            right: Box::new(right),
-        })
+        match chars.get(*index - 1) {
+            Some('[') => {
+                let left = Element::parse(chars, index)?;
+                Element::expect(chars, index, ',')?;
+                let right = Element::parse(chars, index)?;
+                Element::expect(chars, index, ']')?;
+                Ok(Element::Pair(Pair {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                }))
+            }
+            Some(c) => parse_digit(*c)
+                .map(|digit| Element::Number(digit as u16))
+                .map_err(|e| e.at_column(column)),
+            None => Err(Error::invalid("unexpected end of line").at_column(column)),
+        }
+    }
+
+    fn expect(chars: &[char], index: &mut usize, expected: char) -> Result<(), Error> {
+        match chars.get(*index) {
+            Some(c) if *c == expected => {
+                *index += 1;
+                Ok(())
+            }
+            _ => Err(Error::invalid(format!("expected '{}'", expected)).at_column(*index + 1)),
+        }
     }
 
     pub fn magnitude(&self) -> usize {
@@ -171,25 +194,20 @@ impl Pair {
         }
     }
 
-    fn reduced(mut self) -> Self {
-        //println!("Before reducing: {:?}", self);
+    pub fn reduced(mut self) -> Self {
         loop {
             if let Some(result) = self.left.explode(1) {
                 self.right.handle_rightgoing_value(result.1);
-                //println!("Exploded left: {:?}", self);
                 continue;
             }
             if let Some(result) = self.right.explode(1) {
                 self.left.handle_leftgoing_value(result.0);
-                //println!("Exploded right: {:?}", self);
                 continue;
             }
             if self.left.split() {
-                //println!("Split left: {:?}", self);
                 continue;
             }
             if self.right.split() {
-                //println!("Split right: {:?}", self);
                 continue;
             }
             break;
@@ -229,23 +247,20 @@ impl AddAssign for Pair {
     }
 }
 
-impl Sum for Pair {
-    fn sum<I: Iterator<Item = Self>>(mut iter: I) -> Self {
-        let mut sum = iter.next().unwrap();
-        for v in iter {
-            sum += v;
-        }
-        sum
-    }
-}
-
 impl FromStr for Pair {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let element = Element::parse(&s.chars().collect::<Vec<char>>(), &mut 1);
+        let chars = s.chars().collect::<Vec<char>>();
+        let mut index = 0;
+        let element = Element::parse(&chars, &mut index)?;
+        if index < chars.len() {
+            return Err(
+                Error::invalid("unexpected characters after the number").at_column(index + 1)
+            );
+        }
         match element {
-            Element::Number(_) => Err("Somehow, a number was the result"),
+            Element::Number(_) => Err(Error::invalid("expected a pair, got a regular number")),
             Element::Pair(p) => Ok(p),
         }
     }
@@ -296,7 +311,15 @@ mod tests {
     }
 
     #[test]
-    fn it_explodes_left_side() -> Result<(), String> {
+    fn it_rejects_invalid_pairs() {
+        assert_eq!(Some(6), Pair::from_str("[1,[2;3]]").err().and_then(|e| e.column));
+        assert_eq!(Some(6), Pair::from_str("[1,2]]").err().and_then(|e| e.column));
+        assert!(Pair::from_str("[1,").is_err());
+        assert!(Pair::from_str("7").is_err());
+    }
+
+    #[test]
+    fn it_explodes_left_side() -> Result<(), Error> {
         let pair: Pair = "[[[[[9,8],1],2],3],4]".parse()?;
         let reduced = pair.reduced();
         assert_eq!(Pair::from_str("[[[[0,9],2],3],4]")?, reduced);
@@ -304,14 +327,14 @@ mod tests {
     }
 
     #[test]
-    fn it_explodes_right_side() -> Result<(), String> {
+    fn it_explodes_right_side() -> Result<(), Error> {
         let pair: Pair = "[7,[6,[5,[4,[3,2]]]]]".parse::<Pair>()?;
         let reduced = pair.reduced();
         assert_eq!(Pair::from_str("[7,[6,[5,[7,0]]]]")?, reduced);
         Ok(())
     }
     #[test]
-    fn it_explodes_examples() -> Result<(), String> {
+    fn it_explodes_examples() -> Result<(), Error> {
         assert_eq!(
             Pair::from_str("[[6,[5,[7,0]]],3]")?,
             Pair::from_str("[[6,[5,[4,[3,2]]]],1]")?.reduced()
@@ -324,7 +347,7 @@ mod tests {
     }
 
     #[test]
-    fn it_splits() -> Result<(), String> {
+    fn it_splits() -> Result<(), Error> {
         let mut element: Element = Element::Number(10);
         element.split();
         assert_eq!(Element::Pair(Pair::from_str("[5,5]")?), element);
@@ -335,7 +358,7 @@ mod tests {
     }
 
     #[test]
-    fn it_adds_complex() -> Result<(), String> {
+    fn it_adds_complex() -> Result<(), Error> {
         let a: Pair = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse()?;
         let b: Pair = "[1,1]".parse()?;
         assert_eq!(Pair::from_str("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]")?, a + b);
@@ -344,7 +367,7 @@ mod tests {
     }
 
     #[test]
-    fn it_adds_lists() -> Result<(), String> {
+    fn it_adds_lists() -> Result<(), Error> {
         let input = "[1,1]
         [2,2]
         [3,3]
@@ -354,7 +377,7 @@ mod tests {
             .lines()
             .map(|line| line.trim().parse())
             .collect::<Result<Vec<Pair>, _>>()?;
-        let sum: Pair = list.into_iter().sum();
+        let sum = list.into_iter().reduce(|sum, pair| sum + pair).unwrap();
         assert_eq!(Pair::from_str("[[[[1,1],[2,2]],[3,3]],[4,4]]")?, sum);
 
         let input = "[1,1]
@@ -367,7 +390,7 @@ mod tests {
             .lines()
             .map(|line| line.trim().parse())
             .collect::<Result<Vec<Pair>, _>>()?;
-        let sum: Pair = list.into_iter().sum();
+        let sum = list.into_iter().reduce(|sum, pair| sum + pair).unwrap();
         assert_eq!(Pair::from_str("[[[[3,0],[5,3]],[4,4]],[5,5]]")?, sum);
 
         let input = "[1,1]
@@ -381,13 +404,13 @@ mod tests {
             .lines()
             .map(|line| line.trim().parse())
             .collect::<Result<Vec<Pair>, _>>()?;
-        let sum: Pair = list.into_iter().sum();
+        let sum = list.into_iter().reduce(|sum, pair| sum + pair).unwrap();
         assert_eq!(Pair::from_str("[[[[5,0],[7,4]],[5,5]],[6,6]]")?, sum);
         Ok(())
     }
 
     #[test]
-    fn it_sums_slightly_larger_example() -> Result<(), String> {
+    fn it_sums_slightly_larger_example() -> Result<(), Error> {
         let list: Vec<Pair> = "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
         [7,[[[3,7],[4,3]],[[6,3],[8,8]]]]
         [[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]
@@ -407,7 +430,7 @@ mod tests {
             Pair::from_str("[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]")?,
             sum
         );
-        let sum = list.into_iter().sum();
+        let sum = list.into_iter().reduce(|sum, pair| sum + pair).unwrap();
         assert_eq!(
             Pair::from_str("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]")?,
             sum
@@ -416,7 +439,7 @@ mod tests {
     }
 
     #[test]
-    fn it_calculates_magnitude() -> Result<(), String> {
+    fn it_calculates_magnitude() -> Result<(), Error> {
         assert_eq!(29, Pair::from_str("[9,1]")?.magnitude());
         assert_eq!(21, Pair::from_str("[1,9]")?.magnitude());
         assert_eq!(129, Pair::from_str("[[9,1],[1,9]]")?.magnitude());
@@ -430,7 +453,7 @@ mod tests {
     }
 
     #[test]
-    fn it_solves_example1() -> Result<(), String> {
+    fn it_solves_example1() -> Result<(), Error> {
         let input = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
         [[[5,[2,8]],4],[5,[[9,9],0]]]
         [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
@@ -445,12 +468,13 @@ mod tests {
         .lines()
         .map(|line| line.trim().parse())
         .collect::<Result<Vec<Pair>, _>>()?;
-        assert_eq!(4140, pairs.into_iter().sum::<Pair>().magnitude());
+        assert_eq!(Ok(4140), Day18::part1(&pairs));
+        assert!(Day18::part1(&Vec::new()).is_err());
         Ok(())
     }
 
     #[test]
-    fn it_solves_example2() -> Result<(), String> {
+    fn it_solves_example2() -> Result<(), Error> {
         let input = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
         [[[5,[2,8]],4],[5,[[9,9],0]]]
         [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
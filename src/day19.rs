@@ -1,172 +1,76 @@
 use std::{
     collections::{HashMap, HashSet},
-    ops::{Add, Sub},
     str::FromStr,
 };
 
 use itertools::Itertools;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let scanners = input
-        .trim()
-        .split("\n\n")
-        .map(|scanner| scanner.parse().unwrap())
-        .collect();
-    let mut scanners = Scanners {
-        scanners,
-        beacons: HashSet::new(),
-    };
-    scanners.get_relative_positions(12);
-    println!("Day 19 part 1: {}", scanners.beacons.len());
-    println!("Day 19 part 2: {}", scanners.get_largest_distance());
-    
-}
-
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Point {
-    x: isize,
-    y: isize,
-    z: isize,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct RelativePosition {
-    point: Point,
-    variant: usize,
-}
-
-impl std::fmt::Debug for Point {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:>5}, {:>5}, {:>5}", self.x, self.y, self.z)
-    }
-}
-
-impl Point {
-    pub fn new(x: isize, y: isize, z: isize) -> Point {
-        Point { x, y, z }
-    }
-
-    fn get_distance(&self, other: &Point) -> Point {
-        self - other
-    }
-    fn get_manhattan_distance(&self, other: &Point) -> usize {
-        ((self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()) as usize
-    }
-
-    fn get_variant(&self, variant: usize) -> Point {
-        let &Point { x, y, z } = self;
-        match variant {
-            1 => Point::new(x, y, z),
-            2 => Point::new(x, -z, y),
-            3 => Point::new(x, z, -y),
-            4 => Point::new(x, -y, -z),
-
-            5 => Point::new(-x, -y, z),
-            6 => Point::new(-x, -z, -y),
-            7 => Point::new(-x, z, y),
-            8 => Point::new(-x, y, -z),
-
-            9 => Point::new(y, -x, z),
-            10 => Point::new(y, z, x),
-            11 => Point::new(y, x, -z),
-            12 => Point::new(y, -z, -x),
+use crate::{
+    error::Error,
+    geometry::{get_rotations, Matrix3, Vector3},
+    parse::parse_lines,
+    solution::Solution,
+};
 
-            13 => Point::new(-y, x, z),
-            14 => Point::new(-y, -z, x),
-            15 => Point::new(-y, z, -x),
-            16 => Point::new(-y, -x, -z),
+pub struct Day19;
 
-            17 => Point::new(z, y, -x),
-            18 => Point::new(z, x, y),
-            19 => Point::new(z, -y, x),
-            20 => Point::new(z, -x, -y),
+impl Solution for Day19 {
+    type Input = Scanners;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-            21 => Point::new(-z, y, x),
-            22 => Point::new(-z, -x, y),
-            23 => Point::new(-z, -y, -x),
-            24 => Point::new(-z, x, -y),
-            _ => unreachable!(),
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let mut scanners = Vec::new();
+        let mut offset = 0;
+        for scanner in input.trim().split("\n\n") {
+            scanners.push(scanner.parse::<Scanner>().map_err(|e| e.below(offset))?);
+            offset += scanner.lines().count() + 1;
         }
-    }
-}
-
-impl FromStr for Point {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coordinates = s
-            .trim()
-            .split(',')
-            .map(|s| s.parse().map_err(|_| "Cannot parse coordinate"))
-            .collect::<Result<Vec<isize>, _>>()?;
-        if let (Some(x), Some(y), Some(z)) =
-            (coordinates.get(0), coordinates.get(1), coordinates.get(2))
-        {
-            Ok(Point::new(*x, *y, *z))
-        } else {
-            Err("Did not get three coordinates")
+        if scanners.is_empty() {
+            return Err(Error::invalid("there are no scanners"));
         }
+        Ok(Scanners {
+            scanners,
+            beacons: HashSet::new(),
+        })
     }
-}
-
-impl Add for &Point {
-    type Output = Point;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Point::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
-    }
-}
 
-impl Sub for &Point {
-    type Output = Point;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Point::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.aligned(12)?.get_beacons().len())
     }
-}
 
-impl Add for Point {
-    type Output = Point;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Point::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.aligned(12)?.get_largest_distance())
     }
 }
 
-impl Sub for Point {
-    type Output = Point;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Point::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
-    }
+/// Where another scanner is, and how it is turned, as an index into [get_rotations]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RelativePosition {
+    point: Vector3,
+    variant: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Scanner {
     name: String,
     other_scanners: HashMap<String, (Vec<usize>, RelativePosition)>,
-    beacons: Vec<Point>,
+    beacons: Vec<Vector3>,
 }
 
 impl FromStr for Scanner {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.trim().lines();
-        let name = lines
-            .next()
-            .ok_or("cannot get first line")?
-            .replace("---", "")
+        let (header, beacons) = s.split_once('\n').unwrap_or((s, ""));
+        let name = header
+            .trim()
+            .strip_prefix("---")
+            .and_then(|name| name.strip_suffix("---"))
+            .ok_or_else(|| Error::invalid("expected a header like '--- scanner 0 ---'").at_line(1))?
             .trim()
             .to_owned();
-        let beacons: Vec<Point> = lines
-            .map(|line| line.parse())
-            .collect::<Result<Vec<Point>, _>>()?;
+        let beacons = parse_lines(beacons, Vector3::from_str).map_err(|e| e.below(1))?;
         Ok(Scanner {
             name,
             other_scanners: HashMap::new(),
@@ -181,12 +85,12 @@ impl Scanner {
         other: &Scanner,
         min_overlap: usize,
     ) -> Option<RelativePosition> {
-        for i in 1..=24 {
-            let mut offsets: HashMap<Point, usize> = HashMap::new();
+        for (i, rotation) in get_rotations().into_iter().enumerate() {
+            let mut offsets: HashMap<Vector3, usize> = HashMap::new();
             for self_beacon in &self.beacons {
                 for other_beacon in &other.beacons {
                     offsets
-                        .entry(self_beacon.get_distance(&other_beacon.get_variant(i)))
+                        .entry(*self_beacon - rotation * *other_beacon)
                         .and_modify(|count| *count += 1)
                         .or_insert(1);
                 }
@@ -198,9 +102,9 @@ impl Scanner {
                 self.other_scanners.insert(
                     other.name.clone(),
                     (
-                        vec![1],
+                        vec![0],
                         RelativePosition {
-                            point: relative_position.clone(),
+                            point: relative_position,
                             variant: i,
                         },
                     ),
@@ -213,28 +117,29 @@ impl Scanner {
         }
         None
     }
-
-    // fn translated(&self, relative_position: &RelativePosition) -> Scanner {
-    //     Scanner {
-    //         name: self.name.clone(),
-    //         other_scanners: HashMap::new(),
-    //         beacons: self
-    //             .beacons
-    //             .iter()
-    //             .cloned()
-    //             .map(|p| p.get_variant(relative_position.variant) + relative_position.point.clone())
-    //             .collect(),
-    //     }
-    // }
 }
 
+#[derive(Debug, Clone)]
 pub struct Scanners {
     scanners: Vec<Scanner>,
-    beacons: HashSet<Point>,
+    beacons: HashSet<Vector3>,
 }
 
 impl Scanners {
-    fn get_relative_positions(&mut self, min_overlap: usize) {
+    pub fn get_beacons(&self) -> &HashSet<Vector3> {
+        &self.beacons
+    }
+
+    /// A copy with every scanner aligned to the first one, fails if some of them do not overlap
+    /// with at least `min_overlap` beacons
+    pub fn aligned(&self, min_overlap: usize) -> Result<Scanners, Error> {
+        let mut scanners = self.clone();
+        scanners.get_relative_positions(min_overlap)?;
+        Ok(scanners)
+    }
+
+    pub fn get_relative_positions(&mut self, min_overlap: usize) -> Result<(), Error> {
+        let rotations: Vec<Matrix3> = get_rotations();
         let others = self.scanners.clone();
         for scanner in self.scanners.iter_mut() {
             for other in &others {
@@ -246,20 +151,20 @@ impl Scanners {
         let others = self.scanners.clone();
         let first = &mut self.scanners[0];
         while first.other_scanners.len() < others.len() - 1 {
+            let known = first.other_scanners.len();
             for other in &others {
-                if first.other_scanners.contains_key(&other.name) {
-                    let other_relative = first.other_scanners.get(&other.name).unwrap().clone();
+                if let Some(other_relative) = first.other_scanners.get(&other.name).cloned() {
                     for (other_other_name, other_other) in &other.other_scanners {
                         if first.other_scanners.contains_key(other_other_name)
                             || &first.name == other_other_name
                         {
                             continue;
                         }
-                        let mut p = other_other.1.point.get_variant(other_relative.1.variant);
+                        let mut p = rotations[other_relative.1.variant] * other_other.1.point;
                         for var in other_relative.0.iter().rev() {
-                            p = p.get_variant(*var);
+                            p = rotations[*var] * p;
                         }
-                        p = other_relative.1.point.clone() + p;
+                        p = other_relative.1.point + p;
                         let rel_pos = RelativePosition {
                             point: p,
                             variant: other_other.1.variant,
@@ -272,12 +177,19 @@ impl Scanners {
                     }
                 }
             }
+            if first.other_scanners.len() == known {
+                return Err(Error::unsolvable(format!(
+                    "only {} of {} scanners overlap with the first one",
+                    known + 1,
+                    others.len()
+                )));
+            }
         }
         let default = (
-            vec![1],
+            vec![0],
             RelativePosition {
-                point: Point::new(0, 0, 0),
-                variant: 1,
+                point: Vector3::ZERO,
+                variant: 0,
             },
         );
         for scanner in &self.scanners {
@@ -286,19 +198,19 @@ impl Scanners {
                     .other_scanners
                     .get(&scanner.name)
                     .unwrap_or(&default);
-                let mut p = beacon.get_variant(path.1.variant);
+                let mut p = rotations[path.1.variant] * *beacon;
                 for var in path.0.iter().rev() {
-                    p = p.get_variant(*var);
+                    p = rotations[*var] * p;
                 }
-                self.beacons.insert(path.1.point.clone() + p);
+                self.beacons.insert(path.1.point + p);
             }
         }
+        Ok(())
     }
 
-    fn get_largest_distance(&self) -> usize {
-        let mut positions: Vec<Point> = self.scanners[0].other_scanners.values().map(|v| v.1.point.clone()).collect();
-        positions.push(Point::new(0,0,0));
-        //println!("{:#?}", positions);
+    pub fn get_largest_distance(&self) -> usize {
+        let mut positions: Vec<Vector3> = self.scanners[0].other_scanners.values().map(|v| v.1.point).collect();
+        positions.push(Vector3::ZERO);
         positions.iter().permutations(2).map(|v| v[0].get_manhattan_distance(v[1])).max().unwrap_or(0)
     }
 }
@@ -462,7 +374,7 @@ mod tests {
     }
 
     #[test]
-    fn it_parses_scanners() -> Result<(), &'static str> {
+    fn it_parses_scanners() -> Result<(), Error> {
         let input = "--- scanner 0 ---
         404,-588,-901
         528,-643,409
@@ -493,10 +405,20 @@ mod tests {
         assert!(scanner.is_ok());
         let scanner = scanner.unwrap();
         assert_eq!("scanner 0", scanner.name);
-        assert_eq!(Point::new(404, -588, -901), scanner.beacons[0]);
+        assert_eq!(Vector3::new(404, -588, -901), scanner.beacons[0]);
         Ok(())
     }
 
+    #[test]
+    fn it_rejects_invalid_scanners() {
+        let error = Day19::parse("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n1,2\n");
+        assert_eq!(Some(5), error.err().and_then(|e| e.line));
+        assert!("scanner 0\n1,2,3".parse::<Scanner>().is_err());
+        // two scanners that don't overlap
+        let scanners = Day19::parse("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6");
+        assert!(Day19::part1(&scanners.unwrap()).is_err());
+    }
+
     #[test]
     fn it_calculates_offsets() {
         let mut scanner0 = get_scanner0();
@@ -507,29 +429,19 @@ mod tests {
             point: relative_position,
             variant: p1variant,
         } = relative_position.unwrap();
-        assert_eq!(Point::new(68, -1246, -43), relative_position);
+        assert_eq!(Vector3::new(68, -1246, -43), relative_position);
         let position_scanner1 = scanner0.get_relative_position_of(&scanner1, 12).unwrap();
         let scanner4 = get_scanner4();
         let position_scanner4 = scanner1.get_relative_position_of(&scanner4, 12);
         assert!(position_scanner4.is_some());
-        //println!("{:?}", position_scanner4);
         let RelativePosition {
             point: p4,
             variant: _p4variant,
         } = position_scanner4.unwrap();
-        let position_scanner4 = position_scanner1.point + p4.get_variant(p1variant);
-        assert_eq!(Point::new(-20, -1133, 1061), position_scanner4);
+        let position_scanner4 = position_scanner1.point + get_rotations()[p1variant] * p4;
+        assert_eq!(Vector3::new(-20, -1133, 1061), position_scanner4);
     }
 
-    // #[test]
-    // fn it_translates() {
-    //     let mut scanner0 = get_scanner0();
-    //     let scanner1 = get_scanner1();
-    //     let relative_position = scanner0.get_relative_position_of(&scanner1, 12).unwrap();
-    //     let translated_scanner1 = scanner1.translated(&relative_position);
-    //     println!("{:#?}", translated_scanner1);
-    // }
-
     #[test]
     fn it_calculates_relative_positions() {
         let scanners = vec![
@@ -543,9 +455,9 @@ mod tests {
             scanners,
             beacons: HashSet::new(),
         };
-        scanners.get_relative_positions(12);
+        scanners.get_relative_positions(12).unwrap();
         assert_eq!(
-            Point::new(68, -1246, -43),
+            Vector3::new(68, -1246, -43),
             scanners.scanners[0]
                 .other_scanners
                 .get("scanner 1")
@@ -554,7 +466,7 @@ mod tests {
                 .point
         );
         assert_eq!(
-            Point::new(1105, -1205, 1229),
+            Vector3::new(1105, -1205, 1229),
             scanners.scanners[0]
                 .other_scanners
                 .get("scanner 2")
@@ -563,7 +475,7 @@ mod tests {
                 .point
         );
         assert_eq!(
-            Point::new(-92, -2380, -20),
+            Vector3::new(-92, -2380, -20),
             scanners.scanners[0]
                 .other_scanners
                 .get("scanner 3")
@@ -572,7 +484,7 @@ mod tests {
                 .point
         );
         assert_eq!(
-            Point::new(-20, -1133, 1061),
+            Vector3::new(-20, -1133, 1061),
             scanners.scanners[0]
                 .other_scanners
                 .get("scanner 4")
@@ -580,7 +492,7 @@ mod tests {
                 .1
                 .point
         );
-        let mut beacons: Vec<Point> = scanners.beacons.iter().cloned().collect();
+        let mut beacons: Vec<Vector3> = scanners.beacons.iter().cloned().collect();
         beacons.sort_unstable();
         println!("{:#?}", beacons);
         assert_eq!(79, scanners.beacons.len());
@@ -599,7 +511,7 @@ mod tests {
             scanners,
             beacons: HashSet::new(),
         };
-        scanners.get_relative_positions(12);
+        scanners.get_relative_positions(12).unwrap();
         assert_eq!(3621, scanners.get_largest_distance());
     }
 }
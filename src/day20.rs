@@ -2,81 +2,81 @@ use std::{fmt::Display, str::FromStr};
 
 use bitvec::prelude::*;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut img_enhancer = ImageEnhancer::from_str(&input).unwrap().with_iterations(2);
-    println!("Day 20 part 1: {}", img_enhancer.do_ticks());
-    let mut img_enhancer = ImageEnhancer::from_str(&input).unwrap().with_iterations(50);
-    println!("Day 20 part 2: {}", img_enhancer.do_ticks());
+use crate::{
+    error::Error,
+    grid::Grid,
+    image::{Draw, Image, BLACK, WHITE},
+    playback::Simulation,
+    solution::Solution,
+};
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = ImageEnhancer;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.with_iterations(2).do_ticks())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(input.with_iterations(50).do_ticks())
+    }
 }
 
 #[derive(Debug)]
-struct ImageEnhancer {
+pub struct ImageEnhancer {
     algorithm: BitVec,
     iterations: usize,
-    image: BitVec,
-    buf: BitVec,
-    width: usize,
-    height: usize,
+    image: Grid<bool>,
+    buf: Grid<bool>,
     iteration: usize,
 }
 
 impl ImageEnhancer {
-    fn new(
-        algorithm: &BitSlice,
-        iterations: usize,
-        image: &BitSlice,
-        width: usize,
-    ) -> ImageEnhancer {
-        let new_width = width + 2 * iterations;
-        let new_height = image.len() / width + 2 * iterations;
-        let mut new_image = BitVec::with_capacity(new_width * new_height);
-        new_image.extend(bitvec![0; iterations * new_width]);
-        for (i, bit) in image.iter().enumerate() {
-            if i % width == 0 {
-                for _ in 0..iterations {
-                    new_image.push(false);
-                }
-            }
-            new_image.push(*bit);
-            if i % width == width - 1 {
-                for _ in 0..iterations {
-                    new_image.push(false);
-                }
+    /// Pads the image with `iterations` dark pixels on every side, enough room to grow into
+    fn new(algorithm: &BitSlice, iterations: usize, image: &Grid<bool>) -> ImageEnhancer {
+        let mut padded = Grid::filled(
+            image.get_width() + 2 * iterations,
+            image.get_height() + 2 * iterations,
+            false,
+        )
+        .expect("a parsed image is at least 1 pixel wide");
+        for (y, row) in image.rows().enumerate() {
+            for (x, &lit) in row.iter().enumerate() {
+                padded[(x + iterations, y + iterations)] = lit;
             }
         }
-        new_image.extend(bitvec![0; iterations * new_width]);
-        let height = new_image.len() / new_width;
         ImageEnhancer {
             algorithm: algorithm.to_bitvec(),
             iterations,
-            image: new_image.clone(),
-            buf: new_image,
-            width: new_width,
-            height,
+            image: padded.clone(),
+            buf: padded,
             iteration: 0,
         }
     }
 
-    fn with_iterations(self, iterations: usize) -> ImageEnhancer {
-        ImageEnhancer::new(&self.algorithm, iterations, &self.image, self.width)
+    pub fn with_iterations(&self, iterations: usize) -> ImageEnhancer {
+        ImageEnhancer::new(&self.algorithm, iterations, &self.image)
     }
 
-    fn do_ticks(&mut self) -> usize {
+    pub fn do_ticks(&mut self) -> usize {
         for _ in 0..self.iterations {
             self.tick();
         }
-        self.image.count_ones()
+        self.image.get_cells().iter().filter(|&&lit| lit).count()
     }
 
     fn tick(&mut self) {
         for i in 0..self.image.len() {
             let v = self.get_hash(i);
-            self.buf.set(i, self.algorithm[v]);
+            self.buf[i] = self.algorithm[v];
         }
         //self.image = self.buf.clone();
         std::mem::swap(&mut self.image, &mut self.buf);
@@ -84,8 +84,8 @@ impl ImageEnhancer {
     }
 
     fn get_hash(&self, i: usize) -> usize {
-        let x = (i % self.width) as isize;
-        let y = (i / self.width) as isize;
+        let (x, y) = self.image.get_position(i);
+        let (x, y) = (x as isize, y as isize);
         let mut hash: BitVec<Msb0> = BitVec::with_capacity(9);
         for y_offset in 0..3 {
             for x_offset in 0..3 {
@@ -98,63 +98,68 @@ impl ImageEnhancer {
     ///
     /// The fallback value will fluctuate between true and false IIF algorithm[0] = 1 and algorithm[511] = 0
     fn get_or(&self, x: isize, y: isize) -> bool {
-        if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize {
-            self.algorithm[0] && self.iteration % 2 != 0
-            //false
-        } else {
-            self.image[(y as usize) * self.width + (x as usize)]
+        match self.image.get(x, y) {
+            Some(&lit) => lit,
+            None => self.algorithm[0] && !self.iteration.is_multiple_of(2),
         }
     }
 }
 
 impl Display for ImageEnhancer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, bit) in self.image.iter().enumerate() {
-            if i > 0 && i % self.width == 0 {
-                writeln!(f)?;
-            }
-            match *bit {
-                true => write!(f, "#")?,
-                false => write!(f, ".")?,
-            };
-        }
-        writeln!(f)
+        let pixels = self.image.map(|&lit| if lit { '#' } else { '.' });
+        write!(f, "{}", pixels)
     }
 }
 
 impl FromStr for ImageEnhancer {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (algorithm, image) = s
             .trim()
             .split_once("\n\n")
-            .ok_or("cannot parse input: no empty line between algorithm and image")?;
-        let algorithm: BitVec = algorithm
-            .trim()
-            .chars()
-            .filter_map(|c| match c {
-                '.' => Some(false),
-                '#' => Some(true),
-                _ => None,
-            })
-            .collect();
-        let width = image
-            .lines()
-            .next()
-            .ok_or("cannot parse first line of image")?
-            .trim()
-            .len();
-        let image: BitVec = image
-            .trim()
+            .ok_or_else(|| Error::invalid("no empty line between algorithm and image"))?;
+        let parse_pixel = |c| match c {
+            '.' => Ok(false),
+            '#' => Ok(true),
+            _ => Err(Error::invalid(format!("expected '.' or '#', got '{}'", c))),
+        };
+        // The algorithm may be wrapped onto several lines
+        let algorithm = algorithm
             .chars()
-            .filter_map(|c| match c {
-                '.' => Some(false),
-                '#' => Some(true),
-                _ => None,
-            })
-            .collect();
-        Ok(ImageEnhancer::new(&algorithm, 0, &image, width))
+            .filter(|c| !c.is_whitespace())
+            .map(parse_pixel)
+            .collect::<Result<BitVec, Error>>()
+            .map_err(|e| e.at_line(1))?;
+        if algorithm.len() != 512 {
+            return Err(Error::invalid(format!(
+                "the algorithm must have 512 pixels, got {}",
+                algorithm.len()
+            ))
+            .at_line(1));
+        }
+        let image = Grid::parse(image, parse_pixel)
+            .map_err(|e| e.below(s.trim().lines().count() - image.lines().count()))?;
+        Ok(ImageEnhancer::new(&algorithm, 0, &image))
+    }
+}
+
+impl Draw for ImageEnhancer {
+    fn draw(&self) -> Image {
+        let (width, pixels) = (self.image.get_width(), self.image.get_cells());
+        Image::from_grid(width, pixels, |&lit| if lit { WHITE } else { BLACK })
+    }
+}
+
+/// Enhances the image as many times as it was padded for
+impl Simulation for ImageEnhancer {
+    fn step(&mut self) -> bool {
+        if self.iteration >= self.iterations {
+            return false;
+        }
+        self.tick();
+        true
     }
 }
 
@@ -187,6 +192,14 @@ mod tests {
         assert_eq!(25, img_enhancer.image.len());
     }
 
+    #[test]
+    fn it_rejects_invalid_input() {
+        let invalid_image = get_example().strip_suffix("..###").unwrap().to_owned() + "..#x#";
+        let error = ImageEnhancer::from_str(&invalid_image).unwrap_err();
+        assert_eq!((Some(13), Some(4)), (error.line, error.column));
+        assert!(ImageEnhancer::from_str("#..#\n\n#..\n.#.").is_err());
+    }
+
     #[test]
     fn it_prints_image() {
         let img_enhancer = ImageEnhancer::from_str(get_example()).unwrap();
@@ -206,7 +219,7 @@ mod tests {
     fn it_resizes() {
         let img_enhancer = ImageEnhancer::from_str(get_example());
         let img_enhancer = img_enhancer.unwrap().with_iterations(2);
-        assert_eq!(9, img_enhancer.width);
+        assert_eq!(9, img_enhancer.image.get_width());
         println!("{}", img_enhancer);
     }
 
@@ -267,9 +280,9 @@ mod tests {
 
     #[test]
     fn it_converts_bitvec_to_number() {
-        let hash: BitVec<Msb0> = vec![true; 9].iter().collect();
+        let hash: BitVec<Msb0> = [true; 9].iter().collect();
         assert_eq!(511, hash.load::<usize>());
-        let hash: BitVec<Msb0> = vec![true, false, true, true, true, true, true, true, true]
+        let hash: BitVec<Msb0> = [true, false, true, true, true, true, true, true, true]
             .iter()
             .collect();
         assert_eq!(0b101111111, hash.load::<usize>());
@@ -1,6 +1,49 @@
-pub fn solve() {
-    let mut game = DiracDice::new(3, 5);
-    println!("Day 21 part 1: {}", game.play());
+use crate::{day21_part2, error::Error, solution::Solution};
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = (usize, usize);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_starting_positions(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(DiracDice::new(input.0, input.1).play())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(day21_part2::get_most_wins(input.0 as u8, input.1 as u8))
+    }
+}
+
+/// Parses the starting positions from lines like `Player 1 starting position: 4`
+pub fn parse_starting_positions(s: &str) -> Result<(usize, usize), Error> {
+    let mut lines = s.trim().lines();
+    let mut parse_player = |player: usize| -> Result<usize, Error> {
+        let line = lines
+            .next()
+            .ok_or_else(|| Error::invalid(format!("missing line for player {}", player)))?;
+        let position = line
+            .trim()
+            .strip_prefix(&format!("Player {} starting position: ", player))
+            .ok_or_else(|| {
+                Error::invalid(format!("unexpected line for player {}: '{}'", player, line))
+            })?;
+        match position.parse() {
+            Ok(position) if (1..=10).contains(&position) => Ok(position),
+            _ => Err(Error::invalid(format!("invalid starting position '{}'", position))),
+        }
+        .map_err(|e| e.at_line(player))
+    };
+    let positions = (parse_player(1)?, parse_player(2)?);
+    if lines.next().is_some() {
+        return Err(Error::invalid("expected exactly two players").at_line(3));
+    }
+    Ok(positions)
 }
 
 struct Player {
@@ -18,7 +61,7 @@ impl Player {
 
     fn move_forward(&mut self, amount: usize) {
         self.position = ((self.position + amount - 1) % 10) + 1;
-        self.score += self.position as usize;
+        self.score += self.position;
     }
 }
 
@@ -27,7 +70,7 @@ enum WhoseTurnIsIt {
     Player2,
 }
 
-struct DiracDice {
+pub struct DiracDice {
     player1: Player,
     player2: Player,
     dice: DeterministicDice,
@@ -36,7 +79,7 @@ struct DiracDice {
 }
 
 impl DiracDice {
-    fn new(player1: usize, player2: usize) -> DiracDice {
+    pub fn new(player1: usize, player2: usize) -> DiracDice {
         DiracDice {
             player1: Player::new(player1),
             player2: Player::new(player2),
@@ -57,13 +100,13 @@ impl DiracDice {
                 &mut self.player2
             }
         };
-        let roll = self.dice.roll_3_times().unwrap();
+        let roll = self.dice.roll_3_times();
         self.die_rolls += 3;
         player.move_forward(roll);
         player.score
     }
 
-    fn play(&mut self) -> usize {
+    pub fn play(&mut self) -> usize {
         while self.player1.score < 1000 && self.player2.score < 1000 {
             self.roll();
         }
@@ -93,8 +136,9 @@ impl DeterministicDice {
         DeterministicDice { value: 0 }
     }
 
-    fn roll_3_times(&mut self) -> Option<usize> {
-        Some(self.next()? + self.next()? + self.next()?)
+    /// The dice never runs out, so this always adds up 3 rolls
+    fn roll_3_times(&mut self) -> usize {
+        self.by_ref().take(3).sum()
     }
 }
 
@@ -102,6 +146,29 @@ impl DeterministicDice {
 mod tests {
     use super::*;
 
+    #[test]
+    fn it_parses_starting_positions() {
+        assert_eq!(
+            Ok((4, 8)),
+            parse_starting_positions(
+                "Player 1 starting position: 4\nPlayer 2 starting position: 8\n"
+            )
+        );
+        assert!(parse_starting_positions("Player 1 starting position: 4").is_err());
+        assert_eq!(
+            Some(2),
+            parse_starting_positions(
+                "Player 1 starting position: 4\nPlayer 2 starting position: 11"
+            )
+            .unwrap_err()
+            .line
+        );
+        assert!(parse_starting_positions(
+            "Player 2 starting position: 4\nPlayer 1 starting position: 8"
+        )
+        .is_err());
+    }
+
     #[test]
     fn it_cycles_the_dice() {
         let mut dice = DeterministicDice::new();
@@ -1,8 +1,8 @@
 use std::ops::{AddAssign, Mul};
 
-pub fn solve() {
-    let wins = roll(3, 0, 5, 0, Player::Player1);
-    println!("Day 21 part 2: {}", wins.0.max(wins.1));
+pub fn get_most_wins(player1_position: u8, player2_position: u8) -> usize {
+    let wins = roll(player1_position, 0, player2_position, 0, Player::Player1);
+    wins.0.max(wins.1)
 }
 
 enum Player {
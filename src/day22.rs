@@ -2,100 +2,160 @@ use std::{collections::HashSet, str::FromStr};
 
 use bitvec::prelude::*;
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let instructions: Vec<Instruction> = input.lines().map(|line| line.parse().unwrap()).collect();
-    let mut reactor = Reactor::new();
-    for instruction in instructions.clone() {
-        reactor.set(instruction);
+use crate::{
+    error::Error,
+    geometry::{Cuboid, Vector3},
+    parse::parse_lines,
+    solution::Solution,
+};
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = Vec<Instruction>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_instructions(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let mut reactor = Reactor::new();
+        for instruction in input.clone() {
+            reactor.set(instruction);
+        }
+        Ok(reactor.count_enabled_cubes())
     }
-    println!("Day 22 part 1: {}", reactor.count_enabled_cubes());
-    let mut reactor = FastReactor::new(instructions);
-    reactor.apply_instructions();
-    println!("Day 22 part 2: {}", reactor.count_enabled_cubes());
 
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        let mut reactor = FastReactor::new(input.clone());
+        reactor.apply_instructions();
+        Ok(reactor.count_enabled_cubes())
+    }
 }
-struct Reactor {
+
+/// Uses the [FastReactor] for both parts. For part 1, the instructions are clamped to the
+/// initialization region first.
+pub struct Day22Fast;
+
+impl Solution for Day22Fast {
+    type Input = Vec<Instruction>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_instructions(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        let mut reactor = FastReactor::new(
+            input
+                .iter()
+                .filter_map(|instruction| instruction.clamped(-50, 50))
+                .collect(),
+        );
+        reactor.apply_instructions();
+        Ok(reactor.count_enabled_cubes())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Day22::part2(input)
+    }
+}
+
+pub fn parse_instructions(input: &str) -> Result<Vec<Instruction>, Error> {
+    parse_lines(input, Instruction::from_str)
+}
+
+pub struct Reactor {
     grid: BitVec,
-    x: (isize, isize),
-    y: (isize, isize),
-    z: (isize, isize),
+    region: Cuboid,
 }
 
 #[derive(PartialEq, Debug, Clone)]
-struct Instruction {
+pub struct Instruction {
     status: bool,
-    x1: isize,
-    x2: isize,
-    y1: isize,
-    y2: isize,
-    z1: isize,
-    z2: isize,
+    cuboid: Cuboid,
 }
 
 
 impl FromStr for Instruction {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (status, range) = s
             .trim()
             .split_once(' ')
-            .ok_or("Cannot split between status and range")?;
-        let status = status == "on";
-        let ranges: Vec<(isize, isize)> = range
+            .ok_or_else(|| Error::invalid("cannot split between status and range"))?;
+        let status = match status {
+            "on" => true,
+            "off" => false,
+            _ => return Err(Error::invalid(format!("expected 'on' or 'off', got '{}'", status))),
+        };
+        let ranges = range
             .trim()
-            .replace("x=", "")
-            .replace("y=", "")
-            .replace("z=", "")
             .split(',')
-            .map(|range| range.trim().split_once("..").unwrap())
-            .map(|(from, to)| (from.parse().unwrap(), to.parse().unwrap()))
-            .collect();
-        Ok(Instruction {
-            status,
-            x1: ranges[0].0,
-            x2: ranges[0].1,
-            y1: ranges[1].0,
-            y2: ranges[1].1,
-            z1: ranges[2].0,
-            z2: ranges[2].1,
+            .zip(["x=", "y=", "z="])
+            .map(|(range, axis)| {
+                let (from, to) = range
+                    .trim()
+                    .strip_prefix(axis)
+                    .and_then(|range| range.split_once(".."))
+                    .ok_or_else(|| Error::invalid(format!("expected range like {}1..2", axis)))?;
+                let (from, to): (isize, isize) = (from.parse()?, to.parse()?);
+                if from > to {
+                    return Err(Error::invalid(format!("empty range {}..{}", from, to)));
+                }
+                Ok((from, to))
+            })
+            .collect::<Result<Vec<(isize, isize)>, Error>>()?;
+        match ranges[..] {
+            [(x1, x2), (y1, y2), (z1, z2)] if range.split(',').count() == 3 => Ok(Instruction {
+                status,
+                cuboid: Cuboid::new(Vector3::new(x1, y1, z1), Vector3::new(x2, y2, z2)),
+            }),
+            _ => Err(Error::invalid("expected ranges for x, y and z")),
+        }
+    }
+}
+
+impl Instruction {
+    /// Restricts the cuboid to `min..=max` on every axis, or `None` if nothing is left.
+    fn clamped(&self, min: isize, max: isize) -> Option<Instruction> {
+        let region = Cuboid::new(Vector3::new(min, min, min), Vector3::new(max, max, max));
+        Some(Instruction {
+            status: self.status,
+            cuboid: self.cuboid.intersection(&region)?,
         })
     }
 }
 
+impl Default for Reactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Reactor {
-    fn new() -> Reactor {
+    pub fn new() -> Reactor {
         Reactor {
             grid: bitvec![0; 101*101*101],
-            x: (-50, 50),
-            y: (-50, 50),
-            z: (-50, 50),
+            region: Cuboid::new(Vector3::new(-50, -50, -50), Vector3::new(50, 50, 50)),
         }
     }
-    fn set(
-        &mut self,
-        Instruction {
-            status,
-            x1,
-            x2,
-            y1,
-            y2,
-            z1,
-            z2,
-        }: Instruction,
-    ) {
-        for x in x1.max(self.x.0)..=x2.min(self.x.1) {
-            for y in y1.max(self.y.0)..=y2.min(self.y.1) {
-                for z in z1.max(self.z.0)..=z2.min(self.z.1) {
+    pub fn set(&mut self, Instruction { status, cuboid }: Instruction) {
+        let Some(Cuboid { min, max }) = cuboid.intersection(&self.region) else {
+            return;
+        };
+        for x in min.x()..=max.x() {
+            for y in min.y()..=max.y() {
+                for z in min.z()..=max.z() {
+                    let offset = Vector3::new(x, y, z) - self.region.min;
                     self.grid.set(
-                        (z - self.z.0) as usize * 101 * 101
-                            + (y - self.y.0) as usize * 101
-                            + (x - self.x.0) as usize,
+                        offset.z() as usize * 101 * 101
+                            + offset.y() as usize * 101
+                            + offset.x() as usize,
                         status,
                     );
                 }
@@ -103,7 +163,7 @@ impl Reactor {
         }
     }
 
-    fn count_enabled_cubes(&self) -> usize {
+    pub fn count_enabled_cubes(&self) -> usize {
         self.grid.count_ones()
     }
 }
@@ -115,7 +175,7 @@ impl Reactor {
 /// 1, 5, 6, 11
 /// We need the following number for each end, since we interpret it exclusive.
 /// We need the number itself for the start, since we interpret it inclusive.
-struct FastReactor {
+pub struct FastReactor {
     grid: BitVec,
     x_values: Vec<isize>,
     y_values: Vec<isize>,
@@ -124,18 +184,18 @@ struct FastReactor {
 }
 
 impl FastReactor {
-    fn new(instructions: Vec<Instruction>) -> FastReactor {
+    pub fn new(instructions: Vec<Instruction>) -> FastReactor {
         let mut x_values: HashSet<isize> = HashSet::new();
         let mut y_values: HashSet<isize> = HashSet::new();
         let mut z_values: HashSet<isize> = HashSet::new();
 
-        for instruction in &instructions {
-            x_values.insert(instruction.x1);
-            x_values.insert(instruction.x2 + 1);
-            y_values.insert(instruction.y1);
-            y_values.insert(instruction.y2 + 1);
-            z_values.insert(instruction.z1);
-            z_values.insert(instruction.z2 + 1);
+        for Instruction { cuboid, .. } in &instructions {
+            x_values.insert(cuboid.min.x());
+            x_values.insert(cuboid.max.x() + 1);
+            y_values.insert(cuboid.min.y());
+            y_values.insert(cuboid.max.y() + 1);
+            z_values.insert(cuboid.min.z());
+            z_values.insert(cuboid.max.z() + 1);
         }
 
         // If we sort them, we can use binary search later.
@@ -154,7 +214,7 @@ impl FastReactor {
         }
     }
 
-    fn apply_instructions(&mut self) {
+    pub fn apply_instructions(&mut self) {
         for i in 0..self.instructions.len() {
             self.apply_instruction(i);
         }
@@ -162,12 +222,18 @@ impl FastReactor {
 
     fn apply_instruction(&mut self, index: usize) {
         let instruction = &self.instructions[index];
-        let x1 = self.x_values.binary_search(&instruction.x1).unwrap();
-        let x2 = self.x_values.binary_search(&(instruction.x2 + 1)).unwrap();
-        let y1 = self.y_values.binary_search(&instruction.y1).unwrap();
-        let y2 = self.y_values.binary_search(&(instruction.y2 + 1)).unwrap();
-        let z1 = self.z_values.binary_search(&instruction.z1).unwrap();
-        let z2 = self.z_values.binary_search(&(instruction.z2 + 1)).unwrap();
+        let Cuboid { min, max } = instruction.cuboid;
+        let find = |values: &[isize], value| {
+            values
+                .binary_search(&value)
+                .expect("the values hold the bounds of every instruction")
+        };
+        let x1 = find(&self.x_values, min.x());
+        let x2 = find(&self.x_values, max.x() + 1);
+        let y1 = find(&self.y_values, min.y());
+        let y2 = find(&self.y_values, max.y() + 1);
+        let z1 = find(&self.z_values, min.z());
+        let z2 = find(&self.z_values, max.z() + 1);
         for x in x1..x2 {
             for y in y1..y2 {
                 for z in z1..z2 {
@@ -190,7 +256,7 @@ impl FastReactor {
         (x, y, z)
     }
 
-    fn count_enabled_cubes(&self) -> usize {
+    pub fn count_enabled_cubes(&self) -> usize {
         let mut sum = 0;
         for i in self.grid.iter_ones() {
             let (x, y, z) = self.get_coordinates(i);
@@ -215,17 +281,23 @@ mod tests {
         assert_eq!(
             Instruction {
                 status: true,
-                x1: -20,
-                x2: 26,
-                y1: -36,
-                y2: 17,
-                z1: -47,
-                z2: 7
+                cuboid: Cuboid::new(Vector3::new(-20, -36, -47), Vector3::new(26, 17, 7)),
             },
             instruction
         );
     }
 
+    #[test]
+    fn it_rejects_invalid_instructions() {
+        assert!("toggle x=1..2,y=1..2,z=1..2".parse::<Instruction>().is_err());
+        assert!("on x=1..2,y=1..2".parse::<Instruction>().is_err());
+        assert!("on x=1..2,y=1..2,z=1..2,w=1..2".parse::<Instruction>().is_err());
+        assert!("on y=1..2,x=1..2,z=1..2".parse::<Instruction>().is_err());
+        assert!("on x=2..1,y=1..2,z=1..2".parse::<Instruction>().is_err());
+        let error = parse_instructions("on x=1..2,y=1..2,z=1..2\non x=1..a,y=1..2,z=1..2");
+        assert_eq!(Some(2), error.unwrap_err().line);
+    }
+
     #[test]
     fn it_turns_on_cubes() {
         let instruction: Instruction = "on x=10..12,y=10..12,z=10..12".parse().unwrap();
@@ -1,17 +1,34 @@
 use std::{fmt::Display, str::FromStr};
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut burrow: Burrow = input.parse().unwrap();
-    println!("Day 23 part 1: {}", burrow.solve());
+use crate::{day23_fast, error::Error, search::dijkstra, solution::Solution};
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Burrow;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const SOLVES_PART2: bool = false;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        input
+            .solve()
+            .ok_or_else(|| Error::unsolvable("the amphipods cannot be organized"))
+    }
+
+    fn part2(_input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Err(Error::unsupported("this solver only handles rooms of depth 2"))
+    }
 }
+
 /**
 This is the burrow:
-```
+```text
 #############
 #abcdefghijk#
 ###l#n#p#r###
@@ -19,12 +36,11 @@ This is the burrow:
   #########
 ```
 */
-#[derive(Clone, Debug, PartialEq)]
-struct Burrow {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Burrow {
     /// Vec: A A B B C C D D
     amphipods: Vec<u8>,
     cost: Vec<usize>,
-    total_cost: usize,
 }
 
 impl Burrow {
@@ -39,17 +55,6 @@ impl Burrow {
     fn is_in_own_room(&self, amphipod_id: usize) -> bool {
         self.get_own_room(amphipod_id)
             .contains(&self.amphipods[amphipod_id])
-        /*match amphipod_id {
-            0 => (self.amphipods[amphipod_id] == 'l' || self.amphipods[amphipod_id] == 'm'),
-            1 => (self.amphipods[amphipod_id] == 'l' || self.amphipods[amphipod_id] == 'm'),
-            2 => (self.amphipods[amphipod_id] == 'n' || self.amphipods[amphipod_id] == 'o'),
-            3 => (self.amphipods[amphipod_id] == 'n' || self.amphipods[amphipod_id] == 'o'),
-            4 => (self.amphipods[amphipod_id] == 'p' || self.amphipods[amphipod_id] == 'q'),
-            5 => (self.amphipods[amphipod_id] == 'p' || self.amphipods[amphipod_id] == 'q'),
-            6 => (self.amphipods[amphipod_id] == 'r' || self.amphipods[amphipod_id] == 's'),
-            7 => (self.amphipods[amphipod_id] == 'r' || self.amphipods[amphipod_id] == 's'),
-            _ => panic!()
-        }*/
     }
 
     fn get_own_room(&self, amphipod_id: usize) -> [u8; 2] {
@@ -58,7 +63,7 @@ impl Burrow {
             2..=3 => [b'n', b'o'],
             4..=5 => [b'p', b'q'],
             6..=7 => [b'r', b's'],
-            _ => panic!(),
+            _ => unreachable!("a burrow has 8 amphipods, not {}", amphipod_id + 1),
         }
     }
 
@@ -95,13 +100,14 @@ impl Burrow {
         true
     }
 
-    fn solve(&mut self) -> usize {
-        //println!("{}", self);
-        let mut min_cost = usize::MAX;
-        if self.is_finished() {
-            //println!("{}", self.total_cost);
-            return self.total_cost;
-        }
+    /// The lowest total energy to organize the amphipods, `None` if they cannot be organized.
+    pub fn solve(&self) -> Option<usize> {
+        dijkstra(self.clone(), Burrow::get_next, Burrow::is_finished).map(|path| path.cost)
+    }
+
+    /// Every burrow one move away, with the energy of the move
+    fn get_next(&self) -> Vec<(Burrow, usize)> {
+        let mut next = Vec::new();
         for amphipod_id in 0..8 {
             if self.is_in_own_room(amphipod_id) {
                 if [b'm', b'o', b'q', b's'].contains(&self.amphipods[amphipod_id]) {
@@ -113,7 +119,7 @@ impl Burrow {
                     .amphipods
                     .iter()
                     .position(|c| *c == lower_slot)
-                    .unwrap();
+                    .expect("the upper slot is only entered after the lower one");
                 if amphipod_id / 2 == amphipod_in_lower_slot / 2 {
                     // below us is only our own type, so we can stay. Otherwise, we will have to move
                     continue;
@@ -123,7 +129,7 @@ impl Burrow {
             if [b'm', b'o', b'q', b's'].contains(&self.amphipods[amphipod_id])
                 && self
                     .amphipods
-                    .contains(&(self.amphipods[amphipod_id] as u8 - 1))
+                    .contains(&(self.amphipods[amphipod_id] - 1))
             {
                 continue;
             }
@@ -137,21 +143,13 @@ impl Burrow {
                     if !self.can_move_to(amphipod_id, own_room[0]) {
                         continue;
                     }
-                    let mut cloned = self.clone();
-                    cloned.amphipods[amphipod_id] = own_room[0];
-                    cloned.total_cost += cloned.cost[amphipod_id]
-                        * Burrow::get_distance(self.amphipods[amphipod_id], own_room[0]);
-                    min_cost = cloned.solve().min(min_cost);
+                    next.push(self.with_move(amphipod_id, own_room[0]));
                 } else {
                     // Lower place not taken, of course we take the lower place
                     if !self.can_move_to(amphipod_id, own_room[1]) {
                         continue;
                     }
-                    let mut cloned = self.clone();
-                    cloned.amphipods[amphipod_id] = own_room[1];
-                    cloned.total_cost += cloned.cost[amphipod_id]
-                        * Burrow::get_distance(self.amphipods[amphipod_id], own_room[1]);
-                    min_cost = cloned.solve().min(min_cost);
+                    next.push(self.with_move(amphipod_id, own_room[1]));
                 }
             } else {
                 // In foreign room, cannot move to own room. Must move to hallway. Or in own room, but foreign amphipod below
@@ -162,19 +160,27 @@ impl Burrow {
                     if !self.can_move_to(amphipod_id, target) {
                         continue;
                     }
-                    let mut cloned = self.clone();
-                    cloned.amphipods[amphipod_id] = target;
-                    cloned.total_cost += cloned.cost[amphipod_id]
-                        * Burrow::get_distance(self.amphipods[amphipod_id], target);
-                    min_cost = cloned.solve().min(min_cost);
+                    next.push(self.with_move(amphipod_id, target));
                 }
             }
         }
-        min_cost
+        next
+    }
+
+    /// The burrow after the amphipod moved to the target, and the energy it used
+    fn with_move(&self, amphipod_id: usize, target: u8) -> (Burrow, usize) {
+        let mut moved = self.clone();
+        moved.amphipods[amphipod_id] = target;
+        // Amphipods of a type are interchangeable, so keep them in order to meet burrows again
+        let pair = amphipod_id / 2 * 2;
+        moved.amphipods[pair..pair + 2].sort_unstable();
+        let energy = self.cost[amphipod_id]
+            * Burrow::get_distance(self.amphipods[amphipod_id], target);
+        (moved, energy)
     }
     /**
     This is the burrow:
-    ```
+    ```text
     #############
     #abcdefghijk#
     ###l#n#p#r###
@@ -204,7 +210,7 @@ impl Burrow {
         };
         sum += b_distance_to_hallway as usize;
         b = b_hallway;
-        sum += (a as isize - b as isize).abs() as usize;
+        sum += (a as isize - b as isize).unsigned_abs();
         sum
     }
 
@@ -339,108 +345,37 @@ impl Display for Burrow {
 }
 
 impl FromStr for Burrow {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.trim().lines();
-        let third_line = lines.nth(2).ok_or("cannot get third line")?;
-        let mut amphipods = vec![0; 8];
-        for (position, c) in third_line.chars().enumerate() {
-            let position = match position {
-                3 => b'l',
-                5 => b'n',
-                7 => b'p',
-                9 => b'r',
-                _ => 0,
-            };
-            match c {
-                'A' => {
-                    if amphipods[0] == 0 {
-                        amphipods[0] = position;
-                    } else {
-                        amphipods[1] = position;
-                    }
-                }
-                'B' => {
-                    if amphipods[2] == 0 {
-                        amphipods[2] = position;
-                    } else {
-                        amphipods[3] = position;
-                    }
-                }
-                'C' => {
-                    if amphipods[4] == 0 {
-                        amphipods[4] = position;
-                    } else {
-                        amphipods[5] = position;
-                    }
-                }
-                'D' => {
-                    if amphipods[6] == 0 {
-                        amphipods[6] = position;
-                    } else {
-                        amphipods[7] = position;
-                    }
-                }
-                _ => {}
-            };
-        }
-        for (position, c) in lines
-            .next()
-            .ok_or("cannot get fourth line")?
-            .chars()
-            .enumerate()
-        {
-            let position = match position {
-                3 => b'm',
-                5 => b'o',
-                7 => b'q',
-                9 => b's',
-                _ => 0,
-            };
-            match c {
-                'A' => {
-                    if amphipods[0] == 0 {
-                        amphipods[0] = position;
-                    } else {
-                        amphipods[1] = position;
-                    }
-                }
-                'B' => {
-                    if amphipods[2] == 0 {
-                        amphipods[2] = position;
-                    } else {
-                        amphipods[3] = position;
-                    }
-                }
-                'C' => {
-                    if amphipods[4] == 0 {
-                        amphipods[4] = position;
-                    } else {
-                        amphipods[5] = position;
-                    }
-                }
-                'D' => {
-                    if amphipods[6] == 0 {
-                        amphipods[6] = position;
-                    } else {
-                        amphipods[7] = position;
-                    }
-                }
-                _ => {}
-            };
+        let burrow: day23_fast::Burrow = s.parse()?;
+        let layout = burrow.get_layout();
+        if layout.width != 11 || layout.rooms != [2, 4, 6, 8] || layout.depth != 2 {
+            return Err(Error::unsupported(
+                "this solver only handles the layout of the puzzle input",
+            ));
         }
-        Ok(Burrow {
-            amphipods,
-            cost: vec![1, 1, 10, 10, 100, 100, 1000, 1000],
-            total_cost: 0,
-        })
+        // Amphipod i and i + 1 are of the same type, in reading order
+        let amphipods = burrow
+            .get_amphipods()
+            .iter()
+            .map(|&(x, y)| match y {
+                0 => b'a' + x,
+                _ => b'l' + (x - 2) + (y - 1),
+            })
+            .collect();
+        let cost = layout
+            .costs
+            .iter()
+            .flat_map(|cost| [*cost as usize; 2])
+            .collect();
+        Ok(Burrow { amphipods, cost })
     }
 }
 
 /**
 This is the burrow:
-```
+```text
 #############
 #abcdefghijk#
 ###l#n#p#r###
@@ -454,13 +389,12 @@ mod tests {
 
     #[test]
     fn it_solves_example1() {
-        let mut burrow = Burrow {
+        let burrow = Burrow {
             //amphipods: vec!['m', 's', 'l', 'p', 'n', 'q', 'o', 'r'],
             amphipods: vec![b'm', b's', b'l', b'p', b'n', b'q', b'o', b'r'],
             cost: vec![1, 1, 10, 10, 100, 100, 1000, 1000],
-            total_cost: 0,
         };
-        assert_eq!(12521, burrow.solve());
+        assert_eq!(Some(12521), burrow.solve());
     }
 
     #[test]
@@ -470,8 +404,8 @@ mod tests {
 ###B#C#B#D###
   #A#D#C#A#
   #########";
-        let mut burrow: Burrow = input.parse().unwrap();
-        assert_eq!(12521, burrow.solve());
+        let burrow: Burrow = input.parse().unwrap();
+        assert_eq!(Some(12521), burrow.solve());
     }
 
     #[test]
@@ -488,8 +422,20 @@ mod tests {
             //amphipods: vec!['m', 's', 'l', 'p', 'n', 'q', 'o', 'r'],
             amphipods: vec![b'm', b's', b'l', b'p', b'n', b'q', b'r', b'o'],
             cost: vec![1, 1, 10, 10, 100, 100, 1000, 1000],
-            total_cost: 0,
         };
         assert_eq!(expected, burrow);
     }
+
+    #[test]
+    fn it_rejects_invalid_burrows() {
+        let error = "#############\n#...........#\n###B#C#B#D###\n  #A#D#X#A#\n  #########"
+            .parse::<Burrow>()
+            .unwrap_err();
+        assert_eq!((Some(4), Some(8)), (error.line, error.column));
+        let error = "#############\n#...........#\n###B#C#B#D###\n  #A#D#B#A#\n  #########"
+            .parse::<Burrow>()
+            .unwrap_err();
+        assert_eq!((Some(4), Some(8)), (error.line, error.column));
+        assert!("#############\n#...........#\n###B#C#B#D###".parse::<Burrow>().is_err());
+    }
 }
@@ -1,25 +1,101 @@
 use std::{
-    collections::HashSet,
     fmt::Display,
     hash::{Hash, Hasher},
     str::FromStr,
-    sync::Mutex,
+    sync::Arc,
 };
-lazy_static! {
-    static ref VISITED: Mutex<HashSet<u64>> = Mutex::new(HashSet::new());
+
+use crate::{
+    error::Error,
+    search::{Path, Search},
+    solution::Solution,
+};
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    /// The burrow as given, and the unfolded burrow for part 2 if it has the layout of the puzzle
+    type Input = (Burrow, Option<Burrow>);
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let burrow: Burrow = input.parse()?;
+        // The folded rows only fit the four rooms of the puzzle input
+        let unfolded = match burrow.get_layout().rooms == [2, 4, 6, 8] {
+            true => Some(unfold(input)?.parse()?),
+            false => None,
+        };
+        Ok((burrow, unfolded))
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        solve(&input.0)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        match &input.1 {
+            Some(unfolded) => solve(unfolded),
+            None => Err(Error::unsupported(
+                "the folded rows of part 2 only fit the four rooms of the puzzle input",
+            )),
+        }
+    }
+}
+
+pub fn solve(burrow: &Burrow) -> Result<u64, Error> {
+    burrow
+        .solve()
+        .map(|plan| plan.cost)
+        .ok_or_else(|| Error::unsolvable("the amphipods cannot be organized"))
+}
+
+/// Inserts the two rows of amphipods that are folded away in the puzzle input.
+pub fn unfold(input: &str) -> Result<String, Error> {
+    let mut lines: Vec<&str> = input.trim_end().lines().collect();
+    if lines.len() < 5 {
+        return Err(Error::invalid("the burrow needs at least two rows of amphipods"));
+    }
+    lines.splice(3..3, ["  #D#C#B#A#", "  #D#B#A#C#"]);
+    Ok(lines.join("\n"))
+}
+
+/// `(x, y)`, x counts from the left end of the hallway, y from the hallway down into a room.
+pub type Position = (u8, u8);
+
+/// The shape of a burrow and the amphipods that live in it, as given by the map.
+///
+/// Amphipod types are named `A`, `B`, ... and belong to the rooms from left to right. Each room
+/// holds `depth` amphipods, so there are as many of every type. Stepping costs 1, 10, 100, ...
+/// energy, unless the map is followed by a line like `A=1 B=5 C=25 D=125`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Number of places in the hallway
+    pub width: u8,
+    /// The x of the room of every type
+    pub rooms: Vec<u8>,
+    /// Number of places in every room
+    pub depth: u8,
+    /// Energy per step of every type
+    pub costs: Vec<u64>,
 }
 
-pub fn solve() {
-    let input = std::fs::read_to_string("resources/day23.txt").unwrap();
-    let mut burrow: Burrow = input.parse().unwrap();
-    println!("Day 23 part 1: {}", burrow.solve());
-    let input = std::fs::read_to_string("resources/day23_part2.txt").unwrap();
-    let mut burrow: Burrow = input.parse().unwrap();
-    println!("Day 23 part 2: {}", burrow.solve());
+impl Layout {
+    /// Places in the hallway where amphipods may stop, that is all but the ones above a room
+    fn get_eligible_hallway_positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.width)
+            .filter(|x| !self.rooms.contains(x))
+            .map(|x| (x, 0))
+    }
+
+    fn get_name(kind: usize) -> char {
+        (b'A' + kind as u8) as char
+    }
 }
+
 /**
-This is the burrow:
-```
+This is the burrow of the puzzle:
+```text
    0123456789a -> x
 ################
  0#...........#
@@ -30,21 +106,42 @@ This is the burrow:
  5  #########
 ```
 */
-#[derive(Clone, Debug, PartialEq, Hash, Eq)]
-struct Burrow {
-    /// Vec: A A B B C C D D
-    amphipods: Vec<u8>,
-    total_cost: u16,
-    amphipods_per_colour: u8,
+#[derive(Clone, Debug)]
+pub struct Burrow {
+    layout: Arc<Layout>,
+    /// `depth` amphipods of every type, A A B B C C D D for the puzzle input. Sorted in reading
+    /// order within every type, so that burrows which only differ in which of two equal
+    /// amphipods is where are the same
+    amphipods: Vec<Position>,
+}
+
+impl PartialEq for Burrow {
+    fn eq(&self, other: &Self) -> bool {
+        (Arc::ptr_eq(&self.layout, &other.layout) || self.layout == other.layout)
+            && self.amphipods == other.amphipods
+    }
+}
+
+impl Eq for Burrow {}
+
+impl Hash for Burrow {
+    /// Only the amphipods, the burrows of one search share their layout
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.amphipods.hash(state);
+    }
 }
 
 impl Burrow {
-    fn get_coordinate(position: u8) -> (u8, u8) {
-        (position / 0xf, position % 0xf)
+    pub fn get_layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn get_amphipods(&self) -> &[Position] {
+        &self.amphipods
     }
 
-    fn get_position(x: u8, y: u8) -> u8 {
-        x * 0xf + y
+    fn get_kind(&self, amphipod_id: usize) -> usize {
+        amphipod_id / self.layout.depth as usize
     }
 
     fn is_finished(&self) -> bool {
@@ -52,100 +149,59 @@ impl Burrow {
     }
 
     fn is_in_hallway(&self, amphipod_id: usize) -> bool {
-        Burrow::get_coordinate(self.amphipods[amphipod_id]).1 == 0
+        self.amphipods[amphipod_id].1 == 0
     }
 
     fn is_in_own_room(&self, amphipod_id: usize) -> bool {
-        let (x, y) = Burrow::get_coordinate(self.amphipods[amphipod_id]);
+        let (x, y) = self.amphipods[amphipod_id];
         y > 0 && x == self.get_own_room_x(amphipod_id)
     }
 
     fn get_own_room_x(&self, amphipod_id: usize) -> u8 {
-        2 * (amphipod_id as u8 / self.amphipods_per_colour) + 2
-    }
-
-    #[allow(clippy::identity_op)]
-    fn get_eligible_hallway_positions() -> [u8; 7] {
-        [0, 1 * 0xf, 3 * 0xf, 5 * 0xf, 7 * 0xf, 9 * 0xf, 0xa * 0xf]
+        self.layout.rooms[self.get_kind(amphipod_id)]
     }
 
-    fn get_own_room(&self, amphipod_id: usize) -> Vec<u8> {
-        let mut result = Vec::with_capacity(self.amphipods_per_colour.into());
-        for i in 0..self.amphipods_per_colour {
-            result.push(Burrow::get_position(
-                self.get_own_room_x(amphipod_id),
-                i + 1,
-            ));
-        }
-        result
+    fn get_own_room(&self, amphipod_id: usize) -> Vec<Position> {
+        let x = self.get_own_room_x(amphipod_id);
+        (1..=self.layout.depth).map(|y| (x, y)).collect()
     }
 
     fn can_move_to_own_room(&self, amphipod_id: usize) -> bool {
         if self.is_foreign_amphipod_in_own_room(amphipod_id) {
             return false;
         }
-        for step in self.get_path(
-            self.amphipods[amphipod_id],
-            Burrow::get_position(self.get_own_room_x(amphipod_id), 1),
-        ) {
-            // let _s = Burrow::get_coordinate(step);
-            if self.amphipods.contains(&step) && self.amphipods[amphipod_id] != step {
-                return false;
-            }
-        }
-        true
+        self.can_move_to(amphipod_id, (self.get_own_room_x(amphipod_id), 1))
     }
 
     fn is_foreign_amphipod_in_own_room(&self, amphipod_id: usize) -> bool {
-        let amphipods_per_colour = self.amphipods_per_colour as usize;
-        //let (x, y) = Burrow::get_coordinate(self.amphipods[amphipod_id]);
-        for amphipod in 0..self.amphipods.len() {
-            if amphipod / amphipods_per_colour == amphipod_id / amphipods_per_colour {
-                // same type
-                continue;
-            }
-            let p = Burrow::get_coordinate(self.amphipods[amphipod]);
-            if p.0 == self.get_own_room_x(amphipod_id) && p.1 > 0 {
-                return true;
-            }
-        }
-        false
+        let kind = self.get_kind(amphipod_id);
+        let room_x = self.get_own_room_x(amphipod_id);
+        (0..self.amphipods.len()).any(|amphipod| {
+            let (x, y) = self.amphipods[amphipod];
+            self.get_kind(amphipod) != kind && x == room_x && y > 0
+        })
     }
 
-    fn can_move_to(&self, amphipod_id: usize, target: u8) -> bool {
-        let path = self.get_path(self.amphipods[amphipod_id], target);
-        for step in path {
-            if self.amphipods.contains(&step) && self.amphipods[amphipod_id] != step {
-                return false;
-            }
-        }
-        true
+    fn can_move_to(&self, amphipod_id: usize, target: Position) -> bool {
+        let start = self.amphipods[amphipod_id];
+        Burrow::get_path(start, target)
+            .iter()
+            .all(|step| *step == start || !self.amphipods.contains(step))
     }
 
-    fn solve(&mut self) -> u16 {
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        self.hash(&mut hasher);
-        let hash = hasher.finish();
+    pub fn solve(&self) -> Option<Plan> {
+        Solver::default().solve(self)
+    }
 
-        //println!("{}", self);
-        let mut v = VISITED.lock().unwrap();
-        if v.contains(&hash) {
-            return u16::MAX;
-        }
-        v.insert(hash);
-        drop(v);
-
-        let mut min_cost = u16::MAX;
-        if self.is_finished() {
-            // println!("{}", self.total_cost);
-            // println!("{}", self);
-            return self.total_cost;
-        }
+    /// The moves the search considers. An amphipod moves straight to the deepest free place in
+    /// its own room if it can, and otherwise out of a room into the hallway.
+    fn get_moves(&self) -> Vec<(usize, Position)> {
+        let mut moves = Vec::new();
         for amphipod_id in 0..self.amphipods.len() {
-            let (amphipod_x, amphipod_y) = Burrow::get_coordinate(self.amphipods[amphipod_id]);
+            let (amphipod_x, amphipod_y) = self.amphipods[amphipod_id];
             let is_in_own_room = self.is_in_own_room(amphipod_id);
             if is_in_own_room {
-                if amphipod_y == self.amphipods_per_colour {
+                if amphipod_y == self.layout.depth {
                     // already in the lowest slot
                     continue;
                 }
@@ -154,7 +210,7 @@ impl Burrow {
                 }
             }
             // check if we are in the lower slot and the upper slot is in use
-            if !self.can_move_to(amphipod_id, Burrow::get_position(amphipod_x, 0)) {
+            if !self.can_move_to(amphipod_id, (amphipod_x, 0)) {
                 continue;
             }
             let can_move_to_own_room = self.can_move_to_own_room(amphipod_id);
@@ -163,245 +219,403 @@ impl Burrow {
             }
             if !is_in_own_room && can_move_to_own_room {
                 let own_room = self.get_own_room(amphipod_id);
-                for position_in_room in own_room.iter().rev() {
-                    if self.amphipods.contains(position_in_room) {
-                        continue;
-                    }
-                    let mut cloned = self.clone();
-                    cloned.amphipods[amphipod_id] = *position_in_room;
-                    let step_cost = 10u16
-                        .pow(amphipod_id as u32 / self.amphipods_per_colour as u32)
-                        * Burrow::get_distance(self.amphipods[amphipod_id], *position_in_room);
-                    cloned.total_cost = cloned.total_cost.saturating_add(step_cost);
-                    // println!(
-                    //     "Walking cost: {}. New total cost: {}",
-                    //     step_cost, cloned.total_cost
-                    // );
-                    min_cost = cloned.solve().min(min_cost);
-                    break;
+                if let Some(target) = own_room
+                    .iter()
+                    .rev()
+                    .find(|position| !self.amphipods.contains(position))
+                {
+                    moves.push((amphipod_id, *target));
                 }
             } else {
-                // In foreign room, cannot move to own room. Must move to hallway. Or in own room, but foreign amphipod below
-                for target in Burrow::get_eligible_hallway_positions() {
-                    if self.amphipods.contains(&target) {
-                        continue;
+                // In foreign room, cannot move to own room. Must move to hallway. Or in own room,
+                // but foreign amphipod below
+                for target in self.layout.get_eligible_hallway_positions() {
+                    if !self.amphipods.contains(&target) && self.can_move_to(amphipod_id, target) {
+                        moves.push((amphipod_id, target));
                     }
-                    if !self.can_move_to(amphipod_id, target) {
-                        continue;
-                    }
-                    let mut cloned = self.clone();
-                    cloned.amphipods[amphipod_id] = target;
-                    let step_cost = 10u16
-                        .pow(amphipod_id as u32 / self.amphipods_per_colour as u32)
-                        * Burrow::get_distance(self.amphipods[amphipod_id], target);
-                    cloned.total_cost = cloned.total_cost.saturating_add(step_cost);
-                    // println!(
-                    //     "Walking cost: {}. New total cost: {}",
-                    //     step_cost, cloned.total_cost
-                    // );
-                    min_cost = cloned.solve().min(min_cost);
                 }
             }
         }
-        min_cost
+        moves
+    }
+
+    /// The burrow after the amphipod walked to the target, and the move it made
+    fn with_move(&self, amphipod_id: usize, target: Position) -> (Burrow, Move) {
+        let depth = self.layout.depth as usize;
+        let kind = self.get_kind(amphipod_id);
+        let from = self.amphipods[amphipod_id];
+        let step = Move {
+            amphipod: Layout::get_name(kind),
+            from,
+            to: target,
+            energy: self.layout.costs[kind].saturating_mul(Burrow::get_distance(from, target)),
+        };
+        let mut moved = self.clone();
+        moved.amphipods[amphipod_id] = target;
+        moved.amphipods[kind * depth..(kind + 1) * depth].sort_unstable_by_key(|&(x, y)| (y, x));
+        (moved, step)
+    }
+
+    /// The move that turns this burrow into the next one, if a single amphipod moved
+    fn get_move_to(&self, next: &Burrow) -> Option<Move> {
+        let from = self.amphipods.iter().find(|p| !next.amphipods.contains(p))?;
+        let to = next.amphipods.iter().find(|p| !self.amphipods.contains(p))?;
+        let amphipod_id = self.amphipods.iter().position(|p| p == from)?;
+        Some(self.with_move(amphipod_id, *to).1)
     }
 
-    fn get_distance(a: u8, b: u8) -> u16 {
-        let a = Burrow::get_coordinate(a);
-        let b = Burrow::get_coordinate(b);
+    fn get_distance(a: Position, b: Position) -> u64 {
         if a.0 == b.0 {
-            (a.1 as i16 - b.1 as i16).abs() as u16
-        } else if a.1 == b.1 && a.1 == 0 {
-            (a.0 as i16 - b.0 as i16).abs() as u16
+            a.1.abs_diff(b.1) as u64
         } else {
-            a.1 as u16 + b.1 as u16 + (a.0 as i16 - b.0 as i16).abs() as u16
+            a.1 as u64 + b.1 as u64 + a.0.abs_diff(b.0) as u64
         }
     }
 
-    fn get_path(&self, a: u8, b: u8) -> Vec<u8> {
+    fn get_path(a: Position, b: Position) -> Vec<Position> {
         let mut path = Vec::new();
-        let a = Burrow::get_coordinate(a);
-        let b = Burrow::get_coordinate(b);
         if a.0 == b.0 {
             for y in a.1.min(b.1)..=a.1.max(b.1) {
-                path.push(Burrow::get_position(a.0, y));
-            }
-        } else if a.1 == b.1 && a.1 == 0 {
-            for x in a.0.min(b.0)..=a.0.max(b.0) {
-                path.push(Burrow::get_position(x, a.1));
+                path.push((a.0, y));
             }
         } else {
             for y in 0..=a.1 {
-                path.push(Burrow::get_position(a.0, y));
+                path.push((a.0, y));
             }
             for x in a.0.min(b.0)..=a.0.max(b.0) {
-                path.push(Burrow::get_position(x, 0));
+                path.push((x, 0));
             }
             for y in 0..=b.1 {
-                path.push(Burrow::get_position(b.0, y));
+                path.push((b.0, y));
             }
         }
         path
     }
 }
 
+/// One amphipod walking from one position to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub amphipod: char,
+    pub from: Position,
+    pub to: Position,
+    pub energy: u64,
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} moves from {:?} to {:?} using {} energy",
+            self.amphipod, self.from, self.to, self.energy
+        )
+    }
+}
+
+/// The cheapest way to organize the amphipods
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub cost: u64,
+    pub moves: Vec<Move>,
+}
+
+impl Plan {
+    /// The burrow before the first move and after every move
+    pub fn get_frames(&self, burrow: &Burrow) -> Vec<Burrow> {
+        let mut frames = vec![burrow.clone()];
+        for step in &self.moves {
+            let last = &frames[frames.len() - 1];
+            match last.amphipods.iter().position(|position| *position == step.from) {
+                Some(amphipod_id) => frames.push(last.with_move(amphipod_id, step.to).0),
+                None => break,
+            }
+        }
+        frames
+    }
+
+    /// Every frame, each followed by the move that leads to the next one
+    pub fn explain(&self, burrow: &Burrow) -> String {
+        let mut explanation = String::new();
+        for (i, frame) in self.get_frames(burrow).iter().enumerate() {
+            explanation += &frame.to_string();
+            if let Some(step) = self.moves.get(i) {
+                explanation += &format!("{}\n\n", step);
+            }
+        }
+        explanation += &format!("Total energy: {}\n", self.cost);
+        explanation
+    }
+}
+
+/// Dijkstra's algorithm over the burrows that the moves lead to. The solver keeps its buffers
+/// until the next solve.
+#[derive(Default)]
+pub struct Solver {
+    search: Search<Burrow, u64>,
+}
+
+impl Solver {
+    /// The cheapest plan, `None` if the amphipods cannot be organized.
+    pub fn solve(&mut self, burrow: &Burrow) -> Option<Plan> {
+        let Path { cost, nodes } = self.search.dijkstra(
+            burrow.clone(),
+            |burrow| {
+                let moves = burrow.get_moves().into_iter();
+                moves
+                    .map(|(amphipod_id, target)| {
+                        let (next, step) = burrow.with_move(amphipod_id, target);
+                        (next, step.energy)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            Burrow::is_finished,
+        )?;
+        let moves = nodes
+            .windows(2)
+            .map(|pair| pair[0].get_move_to(&pair[1]))
+            .collect::<Option<Vec<Move>>>()?;
+        Some(Plan { cost, moves })
+    }
+}
+
 impl Display for Burrow {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "#############")?;
+        let layout = &self.layout;
+        let get_cell = |position: Position| match self.amphipods.iter().position(|a| *a == position)
+        {
+            Some(amphipod) => Layout::get_name(self.get_kind(amphipod)),
+            None => '.',
+        };
+        // Columns start left of the hallway at -1, the wall around the rooms is one wider
+        let first_wall = layout.rooms.iter().min().map_or(0, |x| *x as isize - 1);
+        let last_wall = layout.rooms.iter().max().map_or(0, |x| *x as isize + 1);
+        writeln!(f, "{}", "#".repeat(layout.width as usize + 2))?;
         write!(f, "#")?;
-        for i in 0x0..=0xa {
-            let position = Burrow::get_position(i, 0);
-            match self.amphipods.iter().position(|c| *c == position) {
-                Some(amphipod) => write!(
-                    f,
-                    "{}",
-                    (b'A' + (amphipod / self.amphipods_per_colour as usize) as u8) as char
-                ),
-                None => write!(f, "."),
-            }?;
+        for x in 0..layout.width {
+            write!(f, "{}", get_cell((x, 0)))?;
         }
         writeln!(f, "#")?;
-        for y in 1..=self.amphipods_per_colour {
-            if y == 1 {
-                write!(f, "###")?;
-            } else {
-                write!(f, "  #")?;
-            }
-            match self
-                .amphipods
-                .iter()
-                .position(|c| *c == Burrow::get_position(2, y))
-            {
-                Some(amphipod) => write!(
-                    f,
-                    "{}",
-                    (b'A' + (amphipod / self.amphipods_per_colour as usize) as u8) as char
-                ),
-                None => write!(f, "."),
-            }?;
-            write!(f, "#")?;
-            match self
-                .amphipods
-                .iter()
-                .position(|c| *c == Burrow::get_position(4, y))
-            {
-                Some(amphipod) => write!(
-                    f,
-                    "{}",
-                    (b'A' + (amphipod / self.amphipods_per_colour as usize) as u8) as char
-                ),
-                None => write!(f, "."),
-            }?;
-            write!(f, "#")?;
-            match self
-                .amphipods
-                .iter()
-                .position(|c| *c == Burrow::get_position(6, y))
-            {
-                Some(amphipod) => write!(
-                    f,
-                    "{}",
-                    (b'A' + (amphipod / self.amphipods_per_colour as usize) as u8) as char
-                ),
-                None => write!(f, "."),
-            }?;
-            write!(f, "#")?;
-            match self
-                .amphipods
-                .iter()
-                .position(|c| *c == Burrow::get_position(8, y))
-            {
-                Some(amphipod) => write!(
-                    f,
-                    "{}",
-                    (b'A' + (amphipod / self.amphipods_per_colour as usize) as u8) as char
-                ),
-                None => write!(f, "."),
-            }?;
-            if y == 1 {
-                writeln!(f, "###")?;
-            } else {
-                writeln!(f, "#  ")?;
+        for y in 1..=layout.depth {
+            for x in -1..=layout.width as isize {
+                let c = match layout.rooms.iter().find(|room| **room as isize == x) {
+                    Some(room) => get_cell((*room, y)),
+                    None if y == 1 || (first_wall..=last_wall).contains(&x) => '#',
+                    None => ' ',
+                };
+                write!(f, "{}", c)?;
             }
+            writeln!(f)?;
         }
-
-        writeln!(f, "  #########")
+        writeln!(
+            f,
+            "{}{}",
+            " ".repeat((first_wall + 1) as usize),
+            "#".repeat((last_wall - first_wall + 1) as usize)
+        )
     }
 }
 
 impl FromStr for Burrow {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let number = s.lines().count() - 3;
-        let mut amphipods = vec![0; 4 * number];
-        for (y, line) in s.lines().skip(1).enumerate() {
-            for (position, c) in line.chars().skip(1).enumerate() {
+        let lines: Vec<&str> = s.lines().collect();
+        let hallway = lines
+            .get(1)
+            .ok_or_else(|| Error::invalid("the burrow has no hallway").at_line(2))?;
+        let width = hallway.chars().skip(1).take_while(|c| *c != '#').count();
+        if !hallway.starts_with('#') || width == 0 || !hallway.trim_end().ends_with('#') {
+            return Err(Error::invalid("expected a hallway like #...........#").at_line(2));
+        }
+        // Before any column is stored as a u8
+        if width > u8::MAX as usize {
+            return Err(Error::unsupported("more than 255 places in the hallway").at_line(2));
+        }
+        // The places in the hallway and the rows of rooms below it, with the line and column
+        let mut places: Vec<(usize, usize, char)> = Vec::new();
+        let mut rooms: Option<Vec<u8>> = None;
+        let mut depth = 0;
+        let mut end = lines.len();
+        for (line, row) in lines.iter().enumerate().skip(1) {
+            let error = |column: usize, message: String| {
+                Error::invalid(message).at_line(line + 1).at_column(column + 1)
+            };
+            let mut row_places = Vec::new();
+            for (column, c) in row.char_indices() {
                 match c {
-                    'A' | 'B' | 'C' | 'D' => {
-                        for i in 0..number {
-                            if amphipods[number * (c as u8 - b'A') as usize + i] == 0 {
-                                amphipods[number * (c as u8 - b'A') as usize + i] =
-                                    Burrow::get_position(position as u8, y as u8);
-                                break;
-                            }
-                        }
+                    '#' | ' ' => {}
+                    '.' | 'A'..='Z' if (1..=width).contains(&column) => {
+                        row_places.push((line, column, c))
                     }
-                    _ => {}
-                };
+                    _ => return Err(error(column, format!("unexpected {:?}", c))),
+                }
+            }
+            if line == 1 {
+                places.extend(row_places);
+                continue;
+            }
+            if row_places.is_empty() {
+                end = line + 1;
+                break;
+            }
+            let row_rooms: Vec<u8> = row_places
+                .iter()
+                .map(|(_, column, _)| *column as u8 - 1)
+                .collect();
+            match &rooms {
+                None => rooms = Some(row_rooms),
+                Some(rooms) if *rooms != row_rooms => {
+                    return Err(Error::unsupported("rooms of different depths").at_line(line + 1))
+                }
+                Some(_) => {}
+            }
+            depth += 1;
+            places.extend(row_places);
+        }
+        let rooms = rooms.ok_or_else(|| Error::invalid("the burrow has no rooms"))?;
+        if depth > u8::MAX as usize {
+            return Err(Error::unsupported("more than 255 places in a room"));
+        }
+        if rooms.len() > 26 {
+            return Err(Error::unsupported("more than 26 types of amphipods"));
+        }
+        let costs = parse_costs(&lines[end.min(lines.len())..], rooms.len())
+            .map_err(|e| e.below(end))?;
+        let mut amphipods: Vec<Vec<Position>> = vec![Vec::new(); rooms.len()];
+        for (line, column, c) in places {
+            let error = |message: String| {
+                Error::invalid(message).at_line(line + 1).at_column(column + 1)
+            };
+            if c == '.' {
+                continue;
             }
+            let kind = (c as u8 - b'A') as usize;
+            let same_kind = amphipods.get_mut(kind).ok_or_else(|| {
+                error(format!(
+                    "expected an amphipod A to {}, got {}",
+                    Layout::get_name(rooms.len() - 1),
+                    c
+                ))
+            })?;
+            if same_kind.len() == depth {
+                return Err(error(format!("more than {} amphipods of type {}", depth, c)));
+            }
+            same_kind.push((column as u8 - 1, (line - 1) as u8));
         }
-        let mut cost = vec![0; 4 * number];
-        for i in 0..4 {
-            for j in 0..number {
-                cost[i * number + j] = 10usize.pow(i as u32);
+        for (kind, same_kind) in amphipods.iter().enumerate() {
+            if same_kind.len() != depth {
+                return Err(Error::invalid(format!(
+                    "expected {} amphipods of type {}, got {}",
+                    depth,
+                    Layout::get_name(kind),
+                    same_kind.len()
+                )));
             }
         }
         Ok(Burrow {
-            amphipods,
-            total_cost: 0,
-            amphipods_per_colour: number as u8,
+            layout: Arc::new(Layout {
+                width: width as u8,
+                rooms,
+                depth: depth as u8,
+                costs,
+            }),
+            amphipods: amphipods.concat(),
         })
     }
 }
 
-/**
-This is the burrow:
-```
-   0123456789a -> x
-################
- 0#...........#
- 1###B#C#B#D###
- 2  #D#C#B#A#
- 3  #D#B#A#C#
- 4  #A#D#C#A#
- 5  #########
-```
-*/
+/// Parses the energy per step from lines like `A=1 B=5 C=25 D=125` after the map. Types that are
+/// not listed cost 1, 10, 100, ... in the order of their rooms.
+fn parse_costs(lines: &[&str], kinds: usize) -> Result<Vec<u64>, Error> {
+    let mut costs: Vec<Option<u64>> = vec![None; kinds];
+    for (line, row) in lines.iter().enumerate() {
+        let error = |message: String| Error::invalid(message).at_line(line + 1);
+        for definition in row.split(|c: char| c == ',' || c.is_whitespace()) {
+            if definition.is_empty() {
+                continue;
+            }
+            let (name, cost) = definition
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected a cost like A=1, got '{}'", definition)))?;
+            let kind = match name.as_bytes() {
+                [c @ b'A'..=b'Z'] if ((c - b'A') as usize) < kinds => (c - b'A') as usize,
+                _ => return Err(error(format!("there is no amphipod type '{}'", name))),
+            };
+            costs[kind] = Some(cost.parse().map_err(|e| Error::from(e).at_line(line + 1))?);
+        }
+    }
+    costs
+        .into_iter()
+        .enumerate()
+        .map(|(kind, cost)| {
+            cost.or_else(|| 10u64.checked_pow(kind as u32)).ok_or_else(|| {
+                Error::unsupported(format!(
+                    "type {} needs a cost, there is no default",
+                    Layout::get_name(kind)
+                ))
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Cause;
     fn get_example1_burrow() -> Burrow {
         Burrow {
-            amphipods: vec![
-                Burrow::get_position(2, 2),
-                Burrow::get_position(8, 2),
-                Burrow::get_position(2, 1),
-                Burrow::get_position(6, 1),
-                Burrow::get_position(4, 1),
-                Burrow::get_position(6, 2),
-                Burrow::get_position(8, 1),
-                Burrow::get_position(4, 2),
-            ],
-            total_cost: 0,
-            amphipods_per_colour: 2,
+            layout: Arc::new(Layout {
+                width: 11,
+                rooms: vec![2, 4, 6, 8],
+                depth: 2,
+                costs: vec![1, 10, 100, 1000],
+            }),
+            amphipods: vec![(2, 2), (8, 2), (2, 1), (6, 1), (4, 1), (6, 2), (8, 1), (4, 2)],
         }
     }
 
     #[test]
     fn it_solves_example1() {
-        let mut burrow = get_example1_burrow();
-        assert_eq!(12521, burrow.solve());
+        let burrow = get_example1_burrow();
+        assert_eq!(Some(12521), burrow.solve().map(|plan| plan.cost));
+    }
+
+    #[test]
+    fn it_returns_moves_and_frames() {
+        let burrow = get_example1_burrow();
+        let plan = burrow.solve().unwrap();
+        // Moves can be made in a different order at the same cost, so only check their sum
+        assert_eq!(plan.cost, plan.moves.iter().map(|step| step.energy).sum());
+        let frames = plan.get_frames(&burrow);
+        assert_eq!(plan.moves.len() + 1, frames.len());
+        assert!(frames[frames.len() - 1].is_finished());
+        let step = Move {
+            amphipod: 'B',
+            from: (6, 1),
+            to: (3, 0),
+            energy: 40,
+        };
+        assert_eq!("B moves from (6, 1) to (3, 0) using 40 energy", step.to_string());
+        assert!(plan
+            .explain(&burrow)
+            .starts_with("#############\n#...........#\n###B#C#B#D###\n"));
+    }
+
+    #[test]
+    fn it_solves_repeatedly() {
+        let burrow = get_example1_burrow();
+        let mut solver = Solver::default();
+        let cost = |plan: Option<Plan>| plan.map(|plan| plan.cost);
+        assert_eq!(Some(12521), cost(solver.solve(&burrow)));
+        assert_eq!(Some(12521), cost(solver.solve(&burrow)));
+        let solved: Burrow = "#############
+#...........#
+###A#B#C#D###
+  #A#B#C#D#
+  #########"
+            .parse()
+            .unwrap();
+        assert_eq!(Some(0), cost(solver.solve(&solved)));
     }
 
     #[test]
@@ -419,11 +633,28 @@ mod tests {
     }
 
     #[test]
-    fn it_converts_position_coordinate() {
-        for i in 0..255 {
-            let (x, y) = Burrow::get_coordinate(i);
-            assert_eq!(i, Burrow::get_position(x, y));
-        }
+    fn it_parses_other_layouts() {
+        let input = "###########
+#.........#
+###B#A#C###
+  #A#B#C#
+  #######";
+        let burrow: Burrow = input.parse().unwrap();
+        assert_eq!(
+            Layout {
+                width: 9,
+                rooms: vec![2, 4, 6],
+                depth: 2,
+                costs: vec![1, 10, 100],
+            },
+            *burrow.get_layout()
+        );
+        let lines = |s: &str| s.lines().map(|l| l.trim_end().to_owned()).collect::<Vec<_>>();
+        assert_eq!(lines(input), lines(&burrow.to_string()));
+        assert_eq!(Some(46), burrow.solve().map(|plan| plan.cost));
+        let expensive_a: Burrow = format!("{}\nA=1000, B=1", input).parse().unwrap();
+        assert_eq!(vec![1000, 1, 100], expensive_a.get_layout().costs);
+        assert_eq!(Some(4006), expensive_a.solve().map(|plan| plan.cost));
     }
 
     #[test]
@@ -435,7 +666,42 @@ mod tests {
   #D#B#A#C#
   #A#D#C#A#
   #########";
-        let mut burrow: Burrow = input.parse().unwrap();
-        assert_eq!(44169, burrow.solve());
+        let burrow: Burrow = input.parse().unwrap();
+        assert_eq!(Some(44169), burrow.solve().map(|plan| plan.cost));
+    }
+
+    #[test]
+    fn it_unfolds_input() {
+        let input = std::fs::read_to_string("resources/day23.txt").unwrap();
+        let expected = std::fs::read_to_string("resources/day23_part2.txt").unwrap();
+        assert_eq!(expected.trim_end(), unfold(&input).unwrap());
+        assert!(unfold("#############\n#...........#").is_err());
+    }
+
+    #[test]
+    fn it_rejects_invalid_burrows() {
+        let input = "#############\n#...........#\n###B#C#B#D###\n  #A#D#C#C#\n  #########";
+        assert!(input.parse::<Burrow>().is_err());
+        assert!("#############\n#...........#".parse::<Burrow>().is_err());
+        let position = |input: &str| {
+            let error = input.parse::<Burrow>().unwrap_err();
+            (error.line, error.column)
+        };
+        let input = "#######\n#.....#\n###B#A###\n  #A#E#\n  #####";
+        assert_eq!((Some(4), Some(6)), position(input));
+        let input = "#######\n#.....#\n###B#A###\n  #A#B#\n  #####\nC=5";
+        assert_eq!((Some(6), None), position(input));
+        let input = "#######\n#.....#\n###B#A###\n  #A#B#.#\n  #####";
+        assert_eq!((Some(4), Some(8)), position(input));
+        // Rooms of different depths
+        let input = "#########\n#.......#\n###B#A#.###\n  #A#B#\n  #####";
+        assert_eq!((Some(4), None), position(input));
+        // A room at column 257 would be stored as room 0
+        let hallway = ".".repeat(300);
+        let room = format!("{}A{}", "#".repeat(256), "#".repeat(43));
+        let input = format!("#{}#\n#{}#\n#{}#\n", "#".repeat(300), hallway, room);
+        let error = input.parse::<Burrow>().unwrap_err();
+        assert!(matches!(error.cause, Cause::Unsupported(_)), "{}", error);
+        assert_eq!((Some(2), None), (error.line, error.column));
     }
 }
@@ -0,0 +1,118 @@
+use crate::{day23_fast::Burrow, error::Error};
+
+/// A random burrow with its amphipods in the rooms and an empty hallway, like the puzzle input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub seed: u64,
+    /// The map, as [Burrow] parses it
+    pub map: String,
+    /// The lowest energy to organize the amphipods, `None` if they cannot be organized
+    pub energy: Option<u64>,
+}
+
+/// Generates the burrow for the seed, with `rooms` rooms of the given depth, and solves it.
+pub fn generate(rooms: u8, depth: u8, seed: u64) -> Result<Puzzle, Error> {
+    let map = generate_map(rooms, depth, seed)?;
+    let burrow: Burrow = map.parse()?;
+    Ok(Puzzle {
+        seed,
+        map,
+        energy: burrow.solve().map(|plan| plan.cost),
+    })
+}
+
+/// Places `depth` amphipods of each of the first `rooms` types randomly into the rooms. The
+/// hallway is three places longer than the rooms with the walls between them, like in the puzzle.
+pub fn generate_map(rooms: u8, depth: u8, seed: u64) -> Result<String, Error> {
+    if !(1..=26).contains(&rooms) || depth == 0 {
+        return Err(Error::unsupported(format!(
+            "{} rooms of depth {}, expected 1 to 26 rooms of depth 1 or more",
+            rooms, depth
+        )));
+    }
+    let mut random = Random(seed);
+    let mut amphipods: Vec<char> = (0..rooms)
+        .flat_map(|kind| [(b'A' + kind) as char].repeat(depth as usize))
+        .collect();
+    // Fisher-Yates
+    for i in (1..amphipods.len()).rev() {
+        amphipods.swap(i, random.below(i + 1));
+    }
+    let width = 2 * rooms as usize + 3;
+    let mut map = vec!["#".repeat(width + 2), format!("#{}#", ".".repeat(width))];
+    for (y, row) in amphipods.chunks(rooms as usize).enumerate() {
+        let row = row
+            .iter()
+            .map(char::to_string)
+            .collect::<Vec<_>>()
+            .join("#");
+        map.push(match y {
+            0 => format!("###{}###", row),
+            _ => format!("  #{}#", row),
+        });
+    }
+    map.push(format!("  {}", "#".repeat(2 * rooms as usize + 1)));
+    Ok(map.join("\n"))
+}
+
+/// SplitMix64, which is good enough to shuffle amphipods and needs no dependency
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0` to `n - 1`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day23;
+
+    #[test]
+    fn it_generates_maps() {
+        let map = generate_map(4, 2, 7).unwrap();
+        assert_eq!(map, generate_map(4, 2, 7).unwrap());
+        assert_ne!(map, generate_map(4, 2, 8).unwrap());
+        let lines: Vec<&str> = map.lines().collect();
+        assert_eq!(5, lines.len());
+        assert_eq!("#...........#", lines[1]);
+        assert_eq!("  #########", lines[4]);
+        let burrow: Burrow = map.parse().unwrap();
+        assert_eq!(vec![2, 4, 6, 8], burrow.get_layout().rooms);
+        assert!(generate_map(0, 2, 7).is_err());
+        assert!(generate_map(4, 0, 7).is_err());
+    }
+
+    #[test]
+    fn it_solves_generated_puzzles() {
+        let puzzle = generate(1, 3, 1).unwrap();
+        assert_eq!(Some(0), puzzle.energy);
+        for seed in 0..10 {
+            let puzzle = generate(3, 3, seed).unwrap();
+            assert!(puzzle.energy.is_some(), "seed {}:\n{}", seed, puzzle.map);
+        }
+    }
+
+    #[test]
+    fn it_agrees_with_the_naive_solver() {
+        // The naive solver only handles the layout of the puzzle input, 4 rooms of depth 2
+        for seed in 0..8 {
+            let puzzle = generate(4, 2, seed).unwrap();
+            let burrow: day23::Burrow = puzzle.map.parse().unwrap();
+            let naive = burrow.solve().map(|cost| cost as u64);
+            assert_eq!(puzzle.energy, naive, "seed {}:\n{}", seed, puzzle.map);
+        }
+        let other_layout = generate_map(3, 2, 0).unwrap();
+        assert!(other_layout.parse::<day23::Burrow>().is_err());
+    }
+}
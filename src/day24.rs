@@ -1,36 +1,234 @@
-use crate::day24_interpreted;
-
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let code = generate_code(&input);
-    std::fs::write("src/day24_generated.rs", code).unwrap();
-    println!("Day 24 part 1: {}", 98998519596997u64);
-    println!("Day 24 part 2: {}", 31521119151421u64);
-    println!("Day 24 solved by manually looking at the summarised code.");
+use std::str::FromStr;
+
+use crate::{
+    day24_generated, day24_interpreted, error::Error, parse::parse_lines, solution::Solution,
+};
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Monad;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        input.get_model_number(true)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        input.get_model_number(false)
+    }
 }
 
-#[allow(unused)]
-fn _solve_part1() -> i64 {
-    let mut max = i64::MIN;
-    for i in (11_111_111_111_111u64..=99_999_999_999_999).rev() {
-        if i % 10_000_000 == 0 {
-            println!("{}", i);
+/// Solves like [Day24], then runs the model number through the hand-simplified MONAD in
+/// [day24_interpreted]. That translation was made from `resources/day24.txt`, so other inputs
+/// are rejected.
+pub struct Day24Interpreted;
+
+impl Solution for Day24Interpreted {
+    type Input = Monad;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        check_model_number(input.get_model_number(true)?, day24_interpreted::solve)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        check_model_number(input.get_model_number(false)?, day24_interpreted::solve)
+    }
+}
+
+/// Like [Day24Interpreted], but with the MONAD that [generate_code] translated to Rust.
+pub struct Day24Generated;
+
+impl Solution for Day24Generated {
+    type Input = Monad;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        check_model_number(input.get_model_number(true)?, day24_generated::solve)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        check_model_number(input.get_model_number(false)?, day24_generated::solve)
+    }
+}
+
+type CompiledMonad = fn(ModelNumber) -> Option<(i64, i64, i64, i64)>;
+
+fn check_model_number(model_number: u64, monad: CompiledMonad) -> Result<u64, Error> {
+    match monad(ModelNumber::new(model_number)) {
+        Some((_, _, _, 0)) => Ok(model_number),
+        _ => Err(Error::unsupported(format!(
+            "the translated MONAD rejects {}, it was made for a different input",
+            model_number
+        ))),
+    }
+}
+
+/// One of the 14 blocks of the MONAD, which only differ in three constants:
+/// whether z is divided by 1 or by 26, what is added to x before comparing it to the digit,
+/// and what is added to the digit before pushing it onto z.
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    pops: bool,
+    x_offset: i64,
+    y_offset: i64,
+}
+
+impl FromStr for Block {
+    type Err = Error;
+
+    /// Line numbers of errors are relative to the start of the block.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().map(|line| line.trim()).collect();
+        let constant = |index: usize, prefix: &str| -> Result<i64, Error> {
+            lines
+                .get(index)
+                .and_then(|line| line.strip_prefix(prefix))
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| {
+                    Error::unsupported(format!("expected '{}<number>'", prefix)).at_line(index + 1)
+                })
+        };
+        Ok(Block {
+            pops: match constant(4, "div z ")? {
+                1 => false,
+                26 => true,
+                other => {
+                    return Err(
+                        Error::unsupported(format!("unexpected divisor {} for z", other)).at_line(5)
+                    )
+                }
+            },
+            x_offset: constant(5, "add x ")?,
+            y_offset: constant(15, "add y ")?,
+        })
+    }
+}
+
+/// z is used as a stack of base 26 digits. Every block that does not pop pushes its input digit,
+/// and every block that pops only keeps z from growing if its digit matches the popped one.
+/// Therefore, a valid model number consists of pairs of digits where
+/// `digits[later] == digits[earlier] + difference`.
+#[derive(Debug, Clone, PartialEq)]
+struct Constraint {
+    earlier: usize,
+    later: usize,
+    difference: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monad {
+    constraints: Vec<Constraint>,
+}
+
+impl Monad {
+    pub fn get_model_number(&self, largest: bool) -> Result<u64, Error> {
+        let mut digits = [0i64; 14];
+        for Constraint {
+            earlier,
+            later,
+            difference,
+        } in &self.constraints
+        {
+            if difference.abs() > 8 {
+                return Err(Error::unsolvable(format!(
+                    "digits {} and {} cannot differ by {}",
+                    earlier + 1,
+                    later + 1,
+                    difference
+                )));
+            }
+            let (earlier_digit, later_digit) = match (largest, *difference >= 0) {
+                (true, true) => (9 - difference, 9),
+                (true, false) => (9, 9 + difference),
+                (false, true) => (1, 1 + difference),
+                (false, false) => (1 - difference, 1),
+            };
+            digits[*earlier] = earlier_digit;
+            digits[*later] = later_digit;
+        }
+        Ok(digits
+            .iter()
+            .fold(0, |number, &digit| number * 10 + digit as u64))
+    }
+}
+
+impl FromStr for Monad {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut blocks = Vec::new();
+        let mut offset = 0;
+        for block in s.trim().split("inp w").skip(1) {
+            let block = format!("inp w{}", block);
+            blocks.push(block.parse::<Block>().map_err(|e| e.below(offset))?);
+            offset += block.trim_end().lines().count();
+        }
+        if blocks.len() != 14 {
+            return Err(Error::unsupported(format!("expected 14 blocks, got {}", blocks.len())));
         }
-        if let Some(v) = day24_interpreted::solve(ModelNumber::new(i)) {
-            if v.3 == 0 {
-                println!("{}", i);
-                max = max.max(i.try_into().unwrap());
+        let mut stack: Vec<(usize, &Block)> = Vec::new();
+        let mut constraints = Vec::new();
+        for (later, block) in blocks.iter().enumerate() {
+            if block.pops {
+                let (earlier, pushed) = stack
+                    .pop()
+                    .ok_or_else(|| {
+                        Error::unsupported(format!("block {} pops from an empty stack", later + 1))
+                    })?;
+                constraints.push(Constraint {
+                    earlier,
+                    later,
+                    difference: pushed.y_offset + block.x_offset,
+                });
+            } else {
+                stack.push((later, block));
             }
-        };
+        }
+        if !stack.is_empty() {
+            return Err(Error::unsupported("not every pushed digit is popped again"));
+        }
+        Ok(Monad { constraints })
     }
-    max
 }
 
-fn generate_code(input: &str) -> String {
+/// Translates the ALU program into Rust code. This is how [day24_generated] was created.
+pub fn generate_code(input: &str) -> Result<String, Error> {
+    let statements = parse_lines(input, |line| {
+        let line = line.trim();
+        let mut segments = line.split_ascii_whitespace();
+        let (op, arg1, arg2) = (segments.next(), segments.next(), segments.next());
+        let arg1 = match arg1 {
+            Some(register @ ("w" | "x" | "y" | "z")) => register,
+            _ => return Err(Error::invalid(format!("'{}' needs a register", line))),
+        };
+        let arg2 = arg2.ok_or_else(|| Error::invalid(format!("'{}' needs 2 arguments", line)));
+        Ok(match op {
+            Some("inp") => format!("    {} = input.next()?;\n", arg1),
+            Some("add") => format!("    {} += {};\n", arg1, arg2?),
+            Some("mul") => format!("    {} *= {};\n", arg1, arg2?),
+            Some("div") => format!("    {} /= {};\n", arg1, arg2?),
+            Some("mod") => format!("    {} %= {};\n", arg1, arg2?),
+            Some("eql") => format!("    {} = if {0} == {} {{ 1 }} else {{ 0 }};\n", arg1, arg2?),
+            _ => return Err(Error::invalid(format!("unknown instruction '{}'", line))),
+        })
+    })?;
     let mut out = String::from(
         "
 #[allow(unused)]
@@ -40,41 +238,9 @@ pub fn solve(mut input: impl Iterator<Item = i64>) -> Option<(i64, i64, i64, i64
     let mut y: i64 = 0;
     let mut z: i64 = 0;\n",
     );
-    for line in input.lines() {
-        let mut segments = line.trim().split_ascii_whitespace();
-        let (op, arg1, arg2) = (segments.next(), segments.next(), segments.next());
-        if let Some(op) = op {
-            let arg1 = arg1.unwrap();
-            match op {
-                "inp" => {
-                    out += &format!("    {} = input.next()?;\n", arg1);
-                }
-                "add" => {
-                    let arg2 = arg2.unwrap();
-                    out += &format!("    {} += {};\n", arg1, arg2);
-                }
-                "mul" => {
-                    let arg2 = arg2.unwrap();
-                    out += &format!("    {} *= {};\n", arg1, arg2);
-                }
-                "div" => {
-                    let arg2 = arg2.unwrap();
-                    out += &format!("    {} /= {};\n", arg1, arg2);
-                }
-                "mod" => {
-                    let arg2 = arg2.unwrap();
-                    out += &format!("    {} %= {};\n", arg1, arg2);
-                }
-                "eql" => {
-                    let arg2 = arg2.unwrap();
-                    out += &format!("    {} = if {0} == {} {{ 1 }} else {{ 0 }};\n", arg1, arg2);
-                }
-                _ => {}
-            }
-        }
-    }
+    out += &statements.concat();
     out += "    Some((w,x,y,z))\n}\n";
-    out
+    Ok(out)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -156,61 +322,16 @@ eql z x"
 
     #[test]
     fn it_generates_code() {
-        let input = get_example1();
-        let code = generate_code(&input);
-        println!("{}", code);
-        println!();
-        println!("{}", generate_code(&get_example2()));
-    }
-
-    #[allow(unused)]
-    pub fn generated_example1(
-        mut input: impl Iterator<Item = i64>,
-    ) -> Option<(i64, i64, i64, i64)> {
-        let mut w: i64 = 0;
-        let mut x: i64 = 0;
-        let mut y: i64 = 0;
-        let mut z: i64 = 0;
-        z = input.next()?;
-        x = input.next()?;
-        z *= 3;
-        z = if z == x { 1 } else { 0 };
-        Some((w, x, y, z))
-    }
-
-    #[allow(unused)]
-    pub fn generated_example2(
-        mut input: impl Iterator<Item = i64>,
-    ) -> Option<(i64, i64, i64, i64)> {
-        let mut w: i64 = 0;
-        let mut x: i64 = 0;
-        let mut y: i64 = 0;
-        let mut z: i64 = 0;
-        w = input.next()?;
-        z += w;
-        z %= 2;
-        w /= 2;
-        y += w;
-        y %= 2;
-        w /= 2;
-        x += w;
-        x %= 2;
-        w /= 2;
-        w %= 2;
-        Some((w, x, y, z))
-    }
-
-    #[test]
-    fn it_generated_example1_correctly() {
-        assert_eq!(1, generated_example1(vec![3, 9].into_iter()).unwrap().3);
-        assert_eq!(0, generated_example1(vec![3, 10].into_iter()).unwrap().3);
-        assert_eq!(None, generated_example1(vec![3].into_iter()));
-    }
-
-    #[test]
-    fn it_generated_example2_correctly() {
-        assert_eq!(Some((0, 1, 1, 1)), generated_example2(vec![7].into_iter()));
-        assert_eq!(Some((1, 1, 0, 1)), generated_example2(vec![13].into_iter()));
+        let code = generate_code(&get_example1()).unwrap();
+        assert!(code.contains("    z *= 3;\n    z = if z == x { 1 } else { 0 };\n"));
+        // The generated module is still what the translation makes of the input
+        let input = std::fs::read_to_string("resources/day24.txt").unwrap();
+        let generated = std::fs::read_to_string("src/day24_generated.rs").unwrap();
+        assert_eq!(generated.trim(), generate_code(&input).unwrap().trim());
+        let error = generate_code("inp w\nadd x 1\nmul y").unwrap_err();
+        assert_eq!(Some(3), error.line);
+        assert!(generate_code("add v 1").is_err());
+        assert!(generate_code("jmp w 2").is_err());
     }
 
     #[test]
@@ -224,7 +345,7 @@ eql z x"
                     assert_eq!(generated.3, interpreted.3, "{}", i);
                 }
                 (None, None) => {}
-                _ => assert!(false),
+                _ => panic!("generated and interpreted disagree for {}", i),
             };
         }
     }
@@ -248,4 +369,20 @@ eql z x"
                 .3
         );
     }
+    #[test]
+    fn it_finds_model_numbers() {
+        let input = std::fs::read_to_string("resources/day24.txt").unwrap();
+        let monad: Monad = input.parse().unwrap();
+        assert_eq!(Ok(98998519596997), monad.get_model_number(true));
+        assert_eq!(Ok(31521119151421), monad.get_model_number(false));
+        for largest in [true, false] {
+            let model_number = ModelNumber::new(monad.get_model_number(largest).unwrap());
+            assert_eq!(0, day24_generated::solve(model_number).unwrap().3);
+        }
+    }
+
+    #[test]
+    fn it_rejects_unknown_programs() {
+        assert!(get_example2().parse::<Monad>().is_err());
+    }
 }
@@ -1,80 +1,71 @@
 use std::{str::FromStr, fmt::Display};
 
-pub fn solve() {
-    let input = std::fs::read_to_string(format!(
-        "resources/{}.txt",
-        module_path!().split_once("::").unwrap().1
-    ))
-    .unwrap();
-    let mut region: Region = input.parse().unwrap();
-    println!("Day 25 part 1: {}", region.count_steps_until_no_movement());
+use crate::{
+    error::Error,
+    grid::Grid,
+    image::{Draw, Image, BLACK},
+    playback::Simulation,
+    solution::Solution,
+};
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Region;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const SOLVES_PART2: bool = false;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(input.clone().count_steps_until_no_movement())
+    }
+
+    fn part2(_input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Err(Error::unsupported("there is no second puzzle on day 25"))
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Cucumber {
     Right,
     Down,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Region {
-    grid: Vec<Option<Cucumber>>,
-    width: usize,
-    height: usize,
+    grid: Grid<Option<Cucumber>>,
 }
 
 impl Region {
-    fn tick(&mut self) -> bool {
-        let mut changed = false;
-        for y in 0..self.height {
-            let first_slot = self.grid[y * self.width].clone();
-            let mut x = 0;
-            while x < self.width {
-                if let Some(Cucumber::Right) = self.grid[y * self.width + x] {
-                    if x + 1 == self.width {
-                        if first_slot.is_none() {
-                            self.grid[y * self.width + x] = None;
-                            self.grid[y * self.width] = Some(Cucumber::Right);
-                            changed = true;
-                            x += 1;
-                        }
-                    } else if self.grid[y * self.width + x + 1].is_none() {
-                        self.grid[y * self.width + x] = None;
-                        self.grid[y * self.width + x + 1] = Some(Cucumber::Right);
-                        changed = true;
-                        x += 1;
-                    }
-                }
-                x += 1;
-            }
-        }
+    pub fn tick(&mut self) -> bool {
+        let right = self.move_herd(Cucumber::Right, (1, 0));
+        let down = self.move_herd(Cucumber::Down, (0, 1));
+        right || down
+    }
 
-        for x in 0..self.width {
-            let first_slot = self.grid[x].clone();
-            let mut y = 0;
-            while y < self.height {
-                if let Some(Cucumber::Down) = self.grid[y * self.width + x] {
-                    if y + 1 >= self.height {
-                        if first_slot.is_none() {
-                            self.grid[y * self.width + x] = None;
-                            self.grid[x] = Some(Cucumber::Down);
-                            changed = true;
-                            y += 1;
-                        }
-                    } else if self.grid[(y + 1) * self.width + x].is_none() {
-                        self.grid[y * self.width + x] = None;
-                        self.grid[(y + 1) * self.width + x] = Some(Cucumber::Down);
-                        changed = true;
-                        y += 1;
-                    }
-                }
-                y += 1;
-            }
+    /// Moves all cucumbers of the herd that face a free cell at once. The sea currents carry them
+    /// from one edge to the opposite one.
+    fn move_herd(&mut self, herd: Cucumber, offset: (isize, isize)) -> bool {
+        let moves: Vec<(usize, usize)> = (0..self.grid.len())
+            .filter(|&i| self.grid[i] == Some(herd))
+            .filter_map(|i| {
+                let target = self.grid.get_offset(i, offset, true)?;
+                self.grid[target].is_none().then_some((i, target))
+            })
+            .collect();
+        for &(from, to) in &moves {
+            self.grid[from] = None;
+            self.grid[to] = Some(herd);
         }
-        changed
+        !moves.is_empty()
     }
 
-    fn count_steps_until_no_movement(&mut self) -> usize {
+    pub fn count_steps_until_no_movement(&mut self) -> usize {
         let mut count = 1;
         while self.tick() {
             count += 1;
@@ -83,18 +74,12 @@ impl Region {
     }
 
     fn get_display(&self) -> String {
-        let mut out = String::with_capacity((self.width + 1) * self.height);
-        for (index, cucumber) in self.grid.iter().enumerate() {
-            if index > 0 && index % self.width == 0 {
-                out += "\n";
-            }
-            out += match cucumber {
-                Some(Cucumber::Down) => "v",
-                Some(Cucumber::Right) => ">",
-                None => "."
-            };
-        }
-        out
+        let cells = self.grid.map(|cucumber| match cucumber {
+            Some(Cucumber::Down) => 'v',
+            Some(Cucumber::Right) => '>',
+            None => '.',
+        });
+        cells.to_string().trim_end().to_owned()
     }
 }
 
@@ -105,28 +90,36 @@ impl Display for Region {
 }
 
 impl FromStr for Region {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let width = s.lines().next().ok_or("Cannot split by line")?.trim().len();
-        let height = s.trim().lines().count();
-        let mut grid = Vec::new();
-        for c in s.chars() {
-            match c {
-                'v' => grid.push(Some(Cucumber::Down)),
-                '>' => grid.push(Some(Cucumber::Right)),
-                '.' => grid.push(None),
-                _ => {}
-            };
-        }
-        Ok(Region {
-            grid,
-            width,
-            height,
+        let grid = Grid::parse(s, |c| match c {
+            'v' => Ok(Some(Cucumber::Down)),
+            '>' => Ok(Some(Cucumber::Right)),
+            '.' => Ok(None),
+            _ => Err(Error::invalid(format!("expected 'v', '>' or '.', got '{}'", c))),
+        })?;
+        Ok(Region { grid })
+    }
+}
+
+/// The east-facing herd in orange, the south-facing one in blue
+impl Draw for Region {
+    fn draw(&self) -> Image {
+        Image::from_grid(self.grid.get_width(), self.grid.get_cells(), |cucumber| match cucumber {
+            Some(Cucumber::Right) => [255, 140, 0],
+            Some(Cucumber::Down) => [30, 144, 255],
+            None => BLACK,
         })
     }
 }
 
+impl Simulation for Region {
+    fn step(&mut self) -> bool {
+        self.tick()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,19 +129,23 @@ mod tests {
         let region: Result<Region, _> = input.parse();
         assert!(region.is_ok());
         let region = region.unwrap();
-        assert_eq!(vec![None, None, None, None, Some(Cucumber::Right), Some(Cucumber::Down)], region.grid);
-        assert_eq!(3, region.width);
-        assert_eq!(2, region.height);
+        assert_eq!(
+            vec![None, None, None, None, Some(Cucumber::Right), Some(Cucumber::Down)],
+            region.grid.get_cells()
+        );
+        assert_eq!(3, region.grid.get_width());
+        assert_eq!(2, region.grid.get_height());
         assert_eq!(input, region.get_display());
+        assert_eq!(Some(3), "...\n.>x".parse::<Region>().err().and_then(|e| e.column));
     }
 
     #[test]
     fn it_runs_1d_simulation() {
         let input = "...>>>>>...";
         let mut region: Region = input.parse().unwrap();
-        assert_eq!(true, region.tick());
+        assert!(region.tick());
         assert_eq!("...>>>>.>..", region.get_display());
-        assert_eq!(true, region.tick());
+        assert!(region.tick());
         assert_eq!("...>>>.>.>.", region.get_display());
     }
 
@@ -0,0 +1,133 @@
+use std::{
+    fmt::Display,
+    num::{ParseIntError, TryFromIntError},
+};
+
+/// Error of any puzzle, with the position in the input where it occurred, as far as it is known.
+/// Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub day: Option<u8>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub cause: Cause,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cause {
+    /// The input could not be read
+    Io(String),
+    /// The input does not have the expected format
+    Invalid(String),
+    /// The input was parsed, but there is no answer for it
+    Unsolvable(String),
+    /// The solution does not implement this part
+    Unsupported(String),
+}
+
+impl Error {
+    fn new(cause: Cause) -> Error {
+        Error {
+            day: None,
+            line: None,
+            column: None,
+            cause,
+        }
+    }
+
+    pub fn io(message: impl Into<String>) -> Error {
+        Error::new(Cause::Io(message.into()))
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Error {
+        Error::new(Cause::Invalid(message.into()))
+    }
+
+    pub fn unsolvable(message: impl Into<String>) -> Error {
+        Error::new(Cause::Unsolvable(message.into()))
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Error {
+        Error::new(Cause::Unsupported(message.into()))
+    }
+
+    /// Sets the day, unless it is already known
+    pub fn in_day(mut self, day: u8) -> Error {
+        self.day.get_or_insert(day);
+        self
+    }
+
+    /// Sets the line, unless it is already known
+    pub fn at_line(mut self, line: usize) -> Error {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// Sets the column, unless it is already known
+    pub fn at_column(mut self, column: usize) -> Error {
+        self.column.get_or_insert(column);
+        self
+    }
+
+    /// Moves the line down, for errors in a section that starts `offset` lines into the input
+    pub fn below(mut self, offset: usize) -> Error {
+        if let Some(line) = self.line.as_mut() {
+            *line += offset;
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "Day {}, ", day)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, "column {}, ", column)?;
+        }
+        match &self.cause {
+            Cause::Io(message) => write!(f, "cannot read input: {}", message),
+            Cause::Invalid(message) => write!(f, "invalid input: {}", message),
+            Cause::Unsolvable(message) => write!(f, "no solution: {}", message),
+            Cause::Unsupported(message) => write!(f, "not supported: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseIntError> for Error {
+    fn from(e: ParseIntError) -> Self {
+        Error::invalid(format!("cannot parse number: {}", e))
+    }
+}
+
+impl From<TryFromIntError> for Error {
+    fn from(e: TryFromIntError) -> Self {
+        Error::invalid(format!("number out of range: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_displays_position() {
+        let error = Error::invalid("expected a digit").at_column(3).at_line(2);
+        assert_eq!(
+            "line 2, column 3, invalid input: expected a digit",
+            error.to_string()
+        );
+        assert_eq!(
+            "Day 9, line 2, column 3, invalid input: expected a digit",
+            error.clone().in_day(9).in_day(10).to_string()
+        );
+        assert_eq!(Some(12), error.below(10).line);
+        assert_eq!(None, Error::invalid("empty input").below(10).line);
+    }
+}
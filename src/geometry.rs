@@ -0,0 +1,362 @@
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use crate::error::Error;
+
+/// A point or a direction with integer coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector<const N: usize>(pub [isize; N]);
+
+pub type Vector2 = Vector<2>;
+pub type Vector3 = Vector<3>;
+
+impl Vector<2> {
+    pub const fn new(x: isize, y: isize) -> Vector2 {
+        Vector([x, y])
+    }
+}
+
+impl Vector<3> {
+    pub const fn new(x: isize, y: isize, z: isize) -> Vector3 {
+        Vector([x, y, z])
+    }
+
+    pub fn z(&self) -> isize {
+        self.0[2]
+    }
+}
+
+impl<const N: usize> Vector<N> {
+    pub const ZERO: Vector<N> = Vector([0; N]);
+
+    pub fn x(&self) -> isize {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> isize {
+        self.0[1]
+    }
+
+    /// The sum of the absolute coordinates
+    pub fn get_manhattan_length(&self) -> usize {
+        self.0.iter().map(|c| c.unsigned_abs()).sum()
+    }
+
+    pub fn get_manhattan_distance(&self, other: &Vector<N>) -> usize {
+        (*self - *other).get_manhattan_length()
+    }
+
+    /// -1, 0 or 1 for every coordinate, e.g. the step towards another point on a straight or
+    /// diagonal line
+    pub fn signum(&self) -> Vector<N> {
+        Vector(self.0.map(isize::signum))
+    }
+
+    /// The smaller coordinates of both, on every axis
+    pub fn component_min(&self, other: &Vector<N>) -> Vector<N> {
+        let mut min = *self;
+        for (c, o) in min.0.iter_mut().zip(other.0) {
+            *c = (*c).min(o);
+        }
+        min
+    }
+
+    /// The larger coordinates of both, on every axis
+    pub fn component_max(&self, other: &Vector<N>) -> Vector<N> {
+        -(-*self).component_min(&-*other)
+    }
+}
+
+impl<const N: usize> Default for Vector<N> {
+    fn default() -> Self {
+        Vector::ZERO
+    }
+}
+
+impl<const N: usize> Add for Vector<N> {
+    type Output = Vector<N>;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Vector<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (c, r) in self.0.iter_mut().zip(rhs.0) {
+            *c += r;
+        }
+    }
+}
+
+impl<const N: usize> Sub for Vector<N> {
+    type Output = Vector<N>;
+
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Vector<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (c, r) in self.0.iter_mut().zip(rhs.0) {
+            *c -= r;
+        }
+    }
+}
+
+impl<const N: usize> Neg for Vector<N> {
+    type Output = Vector<N>;
+
+    fn neg(self) -> Self::Output {
+        Vector(self.0.map(|c| -c))
+    }
+}
+
+impl<const N: usize> Mul<isize> for Vector<N> {
+    type Output = Vector<N>;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Vector(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<const N: usize> Index<usize> for Vector<N> {
+    type Output = isize;
+
+    fn index(&self, axis: usize) -> &isize {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Vector<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut isize {
+        &mut self.0[axis]
+    }
+}
+
+/// The coordinates separated by commas, like in the puzzle inputs
+impl<const N: usize> Display for Vector<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (axis, c) in self.0.iter().enumerate() {
+            if axis > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> FromStr for Vector<N> {
+    type Err = Error;
+
+    /// Parses coordinates separated by commas, e.g. `1,-2,3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coordinates = s
+            .trim()
+            .split(',')
+            .map(|c| Ok(c.trim().parse()?))
+            .collect::<Result<Vec<isize>, Error>>()?;
+        let count = coordinates.len();
+        coordinates
+            .try_into()
+            .map(Vector)
+            .map_err(|_| Error::invalid(format!("expected {} coordinates, got {}", N, count)))
+    }
+}
+
+/// An axis-aligned box, from `min` to `max` with both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds<const N: usize> {
+    pub min: Vector<N>,
+    pub max: Vector<N>,
+}
+
+pub type Rectangle = Bounds<2>;
+pub type Cuboid = Bounds<3>;
+
+impl<const N: usize> Bounds<N> {
+    /// The box with both corners, in any order
+    pub fn new(a: Vector<N>, b: Vector<N>) -> Bounds<N> {
+        Bounds {
+            min: a.component_min(&b),
+            max: a.component_max(&b),
+        }
+    }
+
+    /// The smallest box around all points, `None` without any
+    pub fn enclosing(points: impl IntoIterator<Item = Vector<N>>) -> Option<Bounds<N>> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(
+            points.fold(Bounds::new(first, first), |bounds, point| Bounds {
+                min: bounds.min.component_min(&point),
+                max: bounds.max.component_max(&point),
+            }),
+        )
+    }
+
+    pub fn contains(&self, point: &Vector<N>) -> bool {
+        (0..N).all(|axis| (self.min[axis]..=self.max[axis]).contains(&point[axis]))
+    }
+
+    /// The part that is in both boxes, `None` if they do not overlap
+    pub fn intersection(&self, other: &Bounds<N>) -> Option<Bounds<N>> {
+        let intersection = Bounds {
+            min: self.min.component_max(&other.min),
+            max: self.max.component_min(&other.max),
+        };
+        (0..N)
+            .all(|axis| intersection.min[axis] <= intersection.max[axis])
+            .then_some(intersection)
+    }
+
+    /// The number of points in the box
+    pub fn volume(&self) -> usize {
+        (0..N)
+            .map(|axis| self.max[axis].abs_diff(self.min[axis]) + 1)
+            .product()
+    }
+}
+
+/// A 3x3 matrix, applied to vectors from the left
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Matrix3(pub [[isize; 3]; 3]);
+
+impl Matrix3 {
+    pub const IDENTITY: Matrix3 = Matrix3([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    pub fn get_determinant(&self) -> isize {
+        let [a, b, c] = self.0;
+        a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+            + a[2] * (b[0] * c[1] - b[1] * c[0])
+    }
+
+    pub fn transposed(&self) -> Matrix3 {
+        let mut transposed = [[0; 3]; 3];
+        for (row, values) in self.0.iter().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                transposed[column][row] = *value;
+            }
+        }
+        Matrix3(transposed)
+    }
+}
+
+impl Mul<Vector3> for Matrix3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Self::Output {
+        Vector(self.0.map(|row| (0..3).map(|i| row[i] * rhs[i]).sum()))
+    }
+}
+
+impl Mul for Matrix3 {
+    type Output = Matrix3;
+
+    fn mul(self, rhs: Matrix3) -> Self::Output {
+        let columns = rhs.transposed().0;
+        Matrix3(
+            self.0
+                .map(|row| columns.map(|column| (0..3).map(|i| row[i] * column[i]).sum())),
+        )
+    }
+}
+
+/// The 24 ways to turn a cube onto itself without mirroring it, starting with the identity.
+/// Those are the permutations of the axes with any signs that have a determinant of 1.
+pub fn get_rotations() -> Vec<Matrix3> {
+    let permutations = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut rotations = Vec::with_capacity(24);
+    for permutation in permutations {
+        for signs in 0..8 {
+            let mut matrix = [[0; 3]; 3];
+            for (row, axis) in permutation.iter().enumerate() {
+                matrix[row][*axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+            }
+            let matrix = Matrix3(matrix);
+            if matrix.get_determinant() == 1 {
+                rotations.push(matrix);
+            }
+        }
+    }
+    rotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn it_calculates_with_vectors() {
+        let a = Vector2::new(1, -2);
+        let b: Vector2 = "4, 2".parse().unwrap();
+        assert_eq!(Vector2::new(5, 0), a + b);
+        assert_eq!(Vector2::new(-3, -4), a - b);
+        assert_eq!(Vector2::new(-2, 4), a * -2);
+        assert_eq!(7, a.get_manhattan_distance(&b));
+        assert_eq!(Vector2::new(1, 1), (b - a).signum());
+        assert_eq!((-2, 2), (a.component_min(&b).y(), a.component_max(&b).y()));
+        assert_eq!("1,-2", a.to_string());
+        let mut c = Vector3::ZERO;
+        c += Vector3::new(1, 2, 3);
+        c -= Vector3::new(0, 0, 1);
+        assert_eq!((1, 2, 2), (c.x(), c.y(), c.z()));
+        assert!("1,2".parse::<Vector3>().is_err());
+        assert!("1,a".parse::<Vector2>().is_err());
+    }
+
+    #[test]
+    fn it_intersects_boxes() {
+        let a = Cuboid::new(Vector3::new(10, 10, 10), Vector3::new(12, 12, 12));
+        let b = Cuboid::new(Vector3::new(13, 13, 13), Vector3::new(11, 11, 11));
+        assert_eq!(Vector3::new(11, 11, 11), b.min);
+        assert_eq!(27, a.volume());
+        assert_eq!(8, a.intersection(&b).unwrap().volume());
+        assert!(a.contains(&Vector3::new(12, 10, 11)));
+        assert!(!a.contains(&Vector3::new(13, 10, 11)));
+        let c = Cuboid::new(Vector3::new(13, 0, 0), Vector3::new(20, 20, 20));
+        assert_eq!(None, a.intersection(&c));
+        let rectangle = Rectangle::new(Vector2::new(20, -10), Vector2::new(30, -5));
+        assert_eq!(66, rectangle.volume());
+        let points = [
+            Vector2::new(30, -8),
+            Vector2::new(20, -5),
+            Vector2::new(25, -10),
+        ];
+        assert_eq!(Some(rectangle), Rectangle::enclosing(points));
+        assert_eq!(None, Rectangle::enclosing([]));
+    }
+
+    #[test]
+    fn it_rotates() {
+        let rotations = get_rotations();
+        assert_eq!(24, rotations.len());
+        assert_eq!(Matrix3::IDENTITY, rotations[0]);
+        let v = Vector3::new(1, 2, 3);
+        let rotated: HashSet<Vector3> = rotations.iter().map(|r| *r * v).collect();
+        assert_eq!(24, rotated.len());
+        // Rotations stay rotations when combined, and never change lengths
+        for &a in &rotations {
+            for &b in &rotations {
+                assert!(rotations.contains(&(a * b)));
+            }
+            assert_eq!(6, (a * v).get_manhattan_length());
+            assert_eq!(Matrix3::IDENTITY, a * a.transposed());
+        }
+    }
+}
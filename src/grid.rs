@@ -0,0 +1,273 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use crate::{error::Error, parse::parse_grid};
+
+/// A rectangular grid, stored row by row. Cells are addressed by their index in the rows, or by
+/// `(x, y)` from the top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+/// Which cells around a cell count as its neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Left, up, right and down
+    Four,
+    /// Also the diagonals
+    Eight,
+}
+
+impl Neighbourhood {
+    pub fn get_offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::Four => &[(-1, 0), (0, -1), (1, 0), (0, 1)],
+            Neighbourhood::Eight => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, cells: Vec<T>) -> Result<Grid<T>, Error> {
+        if width == 0 || !cells.len().is_multiple_of(width) {
+            return Err(Error::invalid(format!(
+                "{} cells do not fill rows of {}",
+                cells.len(),
+                width
+            )));
+        }
+        Ok(Grid {
+            width,
+            height: cells.len() / width,
+            cells,
+        })
+    }
+
+    /// Fails for a width of 0, like [Grid::new]
+    pub fn filled(width: usize, height: usize, value: T) -> Result<Grid<T>, Error>
+    where
+        T: Clone,
+    {
+        Grid::new(width, vec![value; width * height])
+    }
+
+    /// Parses one cell per character, see [parse_grid]
+    pub fn parse(
+        input: &str,
+        parse_cell: impl FnMut(char) -> Result<T, Error>,
+    ) -> Result<Self, Error> {
+        let (width, height, cells) = parse_grid(input, parse_cell)?;
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get_cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn get_cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn get_index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    /// `(x, y)` of the index
+    pub fn get_position(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    /// `None` outside of the grid
+    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(&self.cells[self.get_index(x as usize, y as usize)])
+        }
+    }
+
+    /// The index `dx` to the right and `dy` down of the index. Without wrapping, `None` if that
+    /// is outside of the grid, with wrapping the grid repeats in every direction.
+    pub fn get_offset(&self, index: usize, (dx, dy): (isize, isize), wrap: bool) -> Option<usize> {
+        let (x, y) = self.get_position(index);
+        let (x, y) = (x as isize + dx, y as isize + dy);
+        let (width, height) = (self.width as isize, self.height as isize);
+        if wrap {
+            Some(self.get_index(x.rem_euclid(width) as usize, y.rem_euclid(height) as usize))
+        } else if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some(self.get_index(x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// The indices of the neighbours within the grid
+    pub fn get_neighbours(&self, index: usize, neighbourhood: Neighbourhood) -> Vec<usize> {
+        neighbourhood
+            .get_offsets()
+            .iter()
+            .filter_map(|&offset| self.get_offset(index, offset, false))
+            .collect()
+    }
+
+    /// The indices of the neighbours, where the left edge touches the right one and the top edge
+    /// the bottom one
+    pub fn get_neighbours_wrapping(
+        &self,
+        index: usize,
+        neighbourhood: Neighbourhood,
+    ) -> Vec<usize> {
+        neighbourhood
+            .get_offsets()
+            .iter()
+            .filter_map(|&offset| self.get_offset(index, offset, true))
+            .collect()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<usize> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.cells[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.cells[index]
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        &self.cells[self.get_index(x, y)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let index = self.get_index(x, y);
+        &mut self.cells[index]
+    }
+}
+
+/// Every row on its own line, with the cells next to each other
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_digit;
+
+    #[test]
+    fn it_parses_and_displays() {
+        let grid = Grid::parse("123\n456\n", parse_digit).unwrap();
+        assert_eq!((3, 2, 6), (grid.get_width(), grid.get_height(), grid.len()));
+        assert_eq!("123\n456\n", grid.to_string());
+        assert_eq!(6, grid[(2, 1)]);
+        assert_eq!(4, grid[3]);
+        assert_eq!(Some(&2), grid.get(1, 0));
+        assert_eq!(None, grid.get(-1, 0));
+        assert_eq!(None, grid.get(3, 0));
+        assert_eq!((0, 1), grid.get_position(3));
+        let error = Grid::parse("12\n3x", parse_digit).unwrap_err();
+        assert_eq!((Some(2), Some(2)), (error.line, error.column));
+        assert!(Grid::new(4, vec![1, 2, 3]).is_err());
+        assert!(Grid::filled(0, 3, 0).is_err());
+        assert_eq!(grid, Grid::new(3, vec![1, 2, 3, 4, 5, 6]).unwrap());
+    }
+
+    #[test]
+    fn it_finds_neighbours() {
+        let grid = Grid::filled(4, 3, 0).unwrap();
+        assert_eq!(vec![1, 4], grid.get_neighbours(0, Neighbourhood::Four));
+        assert_eq!(vec![1, 4, 5], grid.get_neighbours(0, Neighbourhood::Eight));
+        assert_eq!(8, grid.get_neighbours(5, Neighbourhood::Eight).len());
+        assert_eq!(
+            vec![3, 8, 1, 4],
+            grid.get_neighbours_wrapping(0, Neighbourhood::Four)
+        );
+        assert_eq!(Some(8), grid.get_offset(11, (1, 0), true));
+        assert_eq!(None, grid.get_offset(11, (1, 0), false));
+    }
+
+    #[test]
+    fn it_iterates_rows_and_columns() {
+        let grid = Grid::new(3, (0..6).collect()).unwrap();
+        assert_eq!(
+            vec![&[0, 1, 2][..], &[3, 4, 5]],
+            grid.rows().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![&1, &4], grid.column(1).collect::<Vec<_>>());
+        let sums: Vec<i32> = grid.columns().map(|column| column.sum()).collect();
+        assert_eq!(vec![3, 5, 7], sums);
+        assert_eq!("012\n345\n", grid.map(|cell| cell % 10).to_string());
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::{
+    day09, day11::Octopuses, day15, day20::ImageEnhancer, day25::Region, error::Error,
+    playback::Simulation,
+};
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Grids that can be turned into a picture with one pixel per cell
+pub trait Draw {
+    fn draw(&self) -> Image;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row, from the top left
+    pub pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// One pixel per cell of a grid that is stored row by row, in the colour of the cell
+    pub fn from_grid<T>(width: usize, cells: &[T], colour: impl Fn(&T) -> Rgb) -> Image {
+        Image {
+            width,
+            height: cells.len().checked_div(width).unwrap_or(0),
+            pixels: cells.iter().map(colour).collect(),
+        }
+    }
+
+    /// Every pixel becomes a square of `factor` pixels
+    pub fn scaled(&self, factor: usize) -> Image {
+        let width = self.width * factor;
+        let mut pixels = Vec::with_capacity(self.pixels.len() * factor * factor);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let row: Vec<Rgb> = row
+                .iter()
+                .flat_map(|&pixel| [pixel].repeat(factor))
+                .collect();
+            for _ in 0..factor {
+                pixels.extend_from_slice(&row);
+            }
+        }
+        Image {
+            width,
+            height: self.height * factor,
+            pixels,
+        }
+    }
+
+    /// Binary PPM (P6), which most image viewers read
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    /// 8-bit RGB PNG. The pixels are stored without compression, which keeps the encoder short.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // Bit depth 8, colour type RGB, default compression, filter and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+        png_chunk(&mut out, b"IHDR", &header);
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            // Filter type none
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+/// An animated GIF that loops forever, showing every frame for `delay` hundredths of a second.
+/// The frames need to be of the same size, and have at most 256 colours between them.
+pub fn to_gif(frames: &[Image], delay: u16) -> Result<Vec<u8>, Error> {
+    let first = frames
+        .first()
+        .ok_or_else(|| Error::unsupported("an animation needs at least one frame"))?;
+    if let Some(frame) = frames
+        .iter()
+        .find(|frame| (frame.width, frame.height) != (first.width, first.height))
+    {
+        return Err(Error::unsupported(format!(
+            "frames of different sizes, {}x{} and {}x{}",
+            first.width, first.height, frame.width, frame.height
+        )));
+    }
+    let (width, height) = match (u16::try_from(first.width), u16::try_from(first.height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => {
+            return Err(Error::unsupported(format!(
+                "{}x{} is too large for a GIF",
+                first.width, first.height
+            )))
+        }
+    };
+    let mut palette: Vec<Rgb> = Vec::new();
+    let mut indices: HashMap<Rgb, u8> = HashMap::new();
+    for &pixel in frames.iter().flat_map(|frame| &frame.pixels) {
+        if let Entry::Vacant(entry) = indices.entry(pixel) {
+            if palette.len() == 256 {
+                return Err(Error::unsupported("a GIF can have at most 256 colours"));
+            }
+            entry.insert(palette.len() as u8);
+            palette.push(pixel);
+        }
+    }
+    // The colour table has 2^bits entries, with at least 2 bits for the LZW codes
+    let bits = (2..=8).find(|bits| palette.len() <= 1 << bits).unwrap_or(8);
+    palette.resize(1 << bits, BLACK);
+
+    let mut out = b"GIF89a".to_vec();
+    out.extend(width.to_le_bytes());
+    out.extend(height.to_le_bytes());
+    // Global colour table with 8 bits per primary colour
+    out.extend([0xf0 | (bits - 1), 0, 0]);
+    out.extend(palette.iter().flatten());
+    // Loop forever
+    out.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+    for frame in frames {
+        out.extend([0x21, 0xf9, 4, 0]);
+        out.extend(delay.to_le_bytes());
+        out.extend([0, 0]);
+        out.push(0x2c);
+        out.extend([0, 0, 0, 0]);
+        out.extend(width.to_le_bytes());
+        out.extend(height.to_le_bytes());
+        out.push(0);
+        out.push(bits);
+        let pixels: Vec<u8> = frame.pixels.iter().map(|pixel| indices[pixel]).collect();
+        for block in lzw(&pixels, bits).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0);
+    }
+    out.push(0x3b);
+    Ok(out)
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// A zlib stream with the data in stored deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+/// Compresses colour indices of `min_size` bits with the variable-length LZW of GIF
+fn lzw(indices: &[u8], min_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_size;
+    let end = clear + 1;
+    let mut writer = BitWriter::default();
+    let mut size = min_size + 1;
+    let mut next = end + 1;
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    writer.write(clear, size);
+    let mut prefix: Option<u16> = None;
+    for &index in indices {
+        let code = match prefix {
+            None => {
+                prefix = Some(index as u16);
+                continue;
+            }
+            Some(code) => code,
+        };
+        if let Some(&longer) = codes.get(&(code, index)) {
+            prefix = Some(longer);
+            continue;
+        }
+        writer.write(code, size);
+        if next < 4096 {
+            codes.insert((code, index), next);
+            next += 1;
+            // The decoder adds its codes one step later, so it only needs the wider codes then
+            if next > 1 << size && size < 12 {
+                size += 1;
+            }
+        } else {
+            writer.write(clear, size);
+            codes.clear();
+            size = min_size + 1;
+            next = end + 1;
+        }
+        prefix = Some(index as u16);
+    }
+    if let Some(code) = prefix {
+        writer.write(code, size);
+    }
+    writer.write(end, size);
+    writer.finish()
+}
+
+/// Writes codes least significant bit first, as GIF expects
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+/// The colour `value / max` of the way from `from` to `to`
+pub fn gradient(from: Rgb, to: Rgb, value: usize, max: usize) -> Rgb {
+    let value = value.min(max);
+    let mut colour = from;
+    for (channel, (from, to)) in colour.iter_mut().zip(from.iter().zip(to)) {
+        let (from, to) = (*from as usize, to as usize);
+        *channel = match max {
+            0 => from,
+            _ => (from * (max - value) + to * value) / max,
+        } as u8;
+    }
+    colour
+}
+
+/// A colour for every id, neighbouring ids get clearly different ones
+pub fn categorical(id: usize) -> Rgb {
+    // Stepping around the colour wheel by the golden angle
+    let hue = (id as f64 * 137.508) % 360.0;
+    let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+    let (r, g, b) = match (hue / 60.0) as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let channel = |value: f64| (64.0 + value * 191.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+/// The days that can be exported
+pub const DAYS: [u8; 5] = [9, 11, 15, 20, 25];
+
+/// Up to `frames` pictures of the day: the steps of a simulation, or a single one of a map
+pub fn get_frames(day: u8, input: &str, frames: usize) -> Result<Vec<Image>, Error> {
+    Ok(match day {
+        9 => vec![input.parse::<day09::Cave>()?.draw()],
+        11 => record(input.parse::<Octopuses>()?, frames),
+        15 => vec![input.parse::<day15::Cave>()?.draw()],
+        // As many enhancements as in part 2
+        20 => record(input.parse::<ImageEnhancer>()?.with_iterations(50), frames),
+        25 => record(input.parse::<Region>()?, frames),
+        _ => {
+            return Err(Error::unsupported(format!(
+                "there is nothing to draw, try one of the days {:?}",
+                DAYS
+            )))
+        }
+    })
+}
+
+fn record(mut simulation: impl Simulation + Draw, frames: usize) -> Vec<Image> {
+    let mut images = vec![simulation.draw()];
+    while images.len() < frames && simulation.step() {
+        images.push(simulation.draw());
+    }
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Image {
+        Image::from_grid(3, &[0, 1, 2, 2, 1, 0], |&v| gradient(BLACK, WHITE, v, 2))
+    }
+
+    /// The decoder of the GIF specification, to check that the encoder agrees with it
+    fn unlzw(data: &[u8], min_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_size;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut size = min_size + 1;
+        let mut out = Vec::new();
+        let mut previous: Option<Vec<u8>> = None;
+        let (mut buffer, mut bits, mut bytes) = (0u32, 0u8, data.iter());
+        loop {
+            while bits < size {
+                buffer |= (*bytes.next().unwrap() as u32) << bits;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << size) - 1)) as usize;
+            buffer >>= size;
+            bits -= size;
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.extend([vec![], vec![]]);
+                size = min_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("unknown code {}", code),
+            };
+            out.extend(&entry);
+            if let Some(previous) = previous {
+                if table.len() < 4096 {
+                    table.push([previous, vec![entry[0]]].concat());
+                }
+            }
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn it_compresses_with_lzw() {
+        for (len, colours) in [(0, 4), (1, 4), (100, 2), (20000, 4), (30000, 256)] {
+            let mut state = 7usize;
+            let indices: Vec<u8> = (0..len)
+                .map(|i| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    // Runs of the same colour as well as noise
+                    match i % 1000 < 500 {
+                        true => 1,
+                        false => ((state >> 33) % colours) as u8,
+                    }
+                })
+                .collect();
+            let bits = if colours == 256 { 8 } else { 2 };
+            assert_eq!(indices, unlzw(&lzw(&indices, bits), bits), "{} pixels", len);
+        }
+    }
+
+    #[test]
+    fn it_writes_ppm() {
+        let ppm = example().to_ppm();
+        assert_eq!(b"P6\n3 2\n255\n\0\0\0\x7f\x7f\x7f", &ppm[..17]);
+        assert_eq!(11 + 18, ppm.len());
+    }
+
+    #[test]
+    fn it_writes_png() {
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+        let png = example().to_png();
+        assert_eq!(
+            b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x03\0\0\0\x02",
+            &png[..24]
+        );
+        assert_eq!(b"\0\0\0\0IEND\xae\x42\x60\x82", &png[png.len() - 12..]);
+        // The zlib header, a single final stored block and the Adler-32 of the data
+        let zlib = zlib_stored(b"Wikipedia");
+        assert_eq!([0x78, 0x01, 1, 9, 0, 0xf6, 0xff], zlib[..7]);
+        assert_eq!(0x11e60398u32.to_be_bytes(), zlib[zlib.len() - 4..]);
+        assert_eq!(2 + 5 + 100_000 + 5 + 4, zlib_stored(&[0; 100_000]).len());
+    }
+
+    #[test]
+    fn it_writes_gif() {
+        let frames = [example(), example().scaled(1)];
+        let gif = to_gif(&frames, 10).unwrap();
+        assert_eq!(b"GIF89a\x03\0\x02\0\xf1", &gif[..11]);
+        assert_eq!(Some(&0x3b), gif.last());
+        assert!(to_gif(&[], 10).is_err());
+        assert!(to_gif(&[example(), example().scaled(2)], 10).is_err());
+        let colourful = Image::from_grid(300, &(0..300).collect::<Vec<_>>(), |&i| {
+            [i as u8, (i / 256) as u8, 0]
+        });
+        assert!(to_gif(&[colourful], 10).is_err());
+    }
+
+    #[test]
+    fn it_scales_and_colours() {
+        let image = example().scaled(2);
+        assert_eq!((6, 4), (image.width, image.height));
+        assert_eq!(image.pixels[..6], image.pixels[6..12]);
+        assert_eq!(WHITE, image.pixels[4]);
+        assert_eq!([127, 127, 127], gradient(BLACK, WHITE, 1, 2));
+        assert_ne!(categorical(0), categorical(1));
+    }
+
+    #[test]
+    fn it_draws_every_day() {
+        for day in DAYS {
+            let input = std::fs::read_to_string(format!("resources/day{:02}.txt", day)).unwrap();
+            let frames = get_frames(day, &input, 3).unwrap();
+            assert!(!frames.is_empty() && frames.len() <= 3, "day {}", day);
+            assert!(to_gif(&frames, 10).is_ok(), "day {}", day);
+        }
+        assert!(get_frames(1, "199", 3).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::error::Error;
+
+/// Where the puzzle input of a day comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    /// `resources/dayNN.txt`
+    Resources,
+    Path(PathBuf),
+    Stdin,
+    /// Input that is already in memory, e.g. in tests
+    Text(String),
+}
+
+impl InputSource {
+    pub fn read(&self, day: u8) -> Result<String, Error> {
+        match self {
+            InputSource::Resources => read_file(&get_resource_path(day)),
+            InputSource::Path(path) => read_file(path),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|e| Error::io(format!("stdin: {}", e)))?;
+                Ok(input)
+            }
+            InputSource::Text(text) => Ok(text.clone()),
+        }
+    }
+}
+
+impl From<&str> for InputSource {
+    /// `-` stands for stdin, everything else is a path
+    fn from(s: &str) -> Self {
+        match s {
+            "-" => InputSource::Stdin,
+            path => InputSource::Path(PathBuf::from(path)),
+        }
+    }
+}
+
+pub fn get_resource_path(day: u8) -> PathBuf {
+    PathBuf::from(format!("resources/day{:02}.txt", day))
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path).map_err(|e| Error::io(format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_from_sources() {
+        let expected = std::fs::read_to_string("resources/day17.txt").unwrap();
+        assert_eq!(Ok(expected.clone()), InputSource::Resources.read(17));
+        assert_eq!(
+            Ok(expected),
+            InputSource::from("resources/day17.txt").read(1)
+        );
+        assert_eq!(
+            Ok("1\n2".to_owned()),
+            InputSource::Text("1\n2".to_owned()).read(1)
+        );
+        assert!(InputSource::from("resources/day99.txt").read(1).is_err());
+    }
+
+    #[test]
+    fn it_parses_sources() {
+        assert_eq!(InputSource::Stdin, InputSource::from("-"));
+        assert_eq!(
+            InputSource::Path(PathBuf::from("input.txt")),
+            InputSource::from("input.txt")
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Solutions of Advent of Code 2021.
+//!
+//! Every day lives in its own module, `day01` to `day25`, plus modules for alternative
+//! implementations like `day06_fast`. Each of them exposes the parsed input types with their solver
+//! methods, and a unit struct implementing [solution::Solution]. [registry::get_days] lists all of
+//! them, and [runner] and [bench] run them like the `aoc2021` binary does.
+//!
+//! ```
+//! use aoc2021::{day01::Day01, day22::FastReactor, solution::Solution};
+//!
+//! let depths = Day01::parse("199\n200\n208\n210\n200\n207")?;
+//! assert_eq!(4, Day01::part1(&depths)?);
+//!
+//! let instructions = aoc2021::day22::parse_instructions("on x=1..2,y=1..2,z=1..2")?;
+//! let mut reactor = FastReactor::new(instructions);
+//! reactor.apply_instructions();
+//! assert_eq!(8, reactor.count_enabled_cubes());
+//! # Ok::<(), aoc2021::error::Error>(())
+//! ```
+
+pub mod answers;
+pub mod bench;
+pub mod cli;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day06_fast;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day21_part2;
+pub mod day22;
+pub mod day23;
+pub mod day23_fast;
+pub mod day23_generator;
+pub mod day24;
+pub mod day24_generated;
+pub mod day24_interpreted;
+pub mod day25;
+pub mod error;
+pub mod geometry;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod parse;
+pub mod playback;
+pub mod registry;
+pub mod runner;
+pub mod search;
+pub mod solution;
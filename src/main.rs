@@ -1,67 +1,59 @@
-use std::time::Instant;
-#[macro_use]
-extern crate lazy_static;
+use aoc2021::{bench, cli, runner};
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-#[allow(dead_code)]
-mod day06;
-mod day06_fast;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day21_part2;
-mod day22;
-#[allow(dead_code)]
-mod day23;
-mod day23_fast;
-mod day24;
-mod day24_generated;
-mod day24_interpreted;
-mod day25;
 fn main() {
-    let start = Instant::now();
-    day01::solve();
-    day02::solve();
-    day03::solve();
-    day04::solve();
-    day05::solve();
-    //day6::solve();
-    day06_fast::solve();
-    day07::solve();
-    day08::solve();
-    day09::solve();
-    day10::solve();
-    day11::solve();
-    day12::solve();
-    day13::solve();
-    day14::solve();
-    day15::solve();
-    day16::solve();
-    day17::solve();
-    day18::solve();
-    day19::solve();
-    day20::solve();
-    day21::solve();
-    day21_part2::solve();
-    day22::solve();
-    day23_fast::solve();
-    day24::solve();
-    day25::solve();
-    println!("Elapsed time: {}ms", start.elapsed().as_millis());
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    match command {
+        cli::Command::Run(options) => {
+            if let Err(e) = runner::run(&options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Verify(options) => match runner::verify(&options) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        cli::Command::Bench(options) => {
+            if let Err(e) = bench::bench(&options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Generate(options) => {
+            if let Err(e) = runner::generate(&options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Play(options) => {
+            if let Err(e) = runner::play(&options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Export(options) => {
+            if let Err(e) = runner::export(&options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Disassemble(options) => {
+            if let Err(e) = runner::disassemble(&options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::List => runner::list(),
+        cli::Command::Help => println!("{}", cli::USAGE),
+    }
 }
@@ -0,0 +1,83 @@
+use crate::error::Error;
+
+/// Parses every line of the input with `parse`, adding the line number to errors.
+pub fn parse_lines<T>(
+    input: &str,
+    mut parse: impl FnMut(&str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse(line).map_err(|e| e.at_line(i + 1)))
+        .collect()
+}
+
+/// Parses a single digit character, e.g. of a height map.
+pub fn parse_digit(c: char) -> Result<u8, Error> {
+    c.to_digit(10)
+        .map(|digit| digit as u8)
+        .ok_or_else(|| Error::invalid(format!("expected a digit, got '{}'", c)))
+}
+
+/// Parses a rectangular grid of characters, e.g. a height map, row by row.
+/// Returns the width, the height and the cells.
+pub fn parse_grid<T>(
+    input: &str,
+    mut parse_cell: impl FnMut(char) -> Result<T, Error>,
+) -> Result<(usize, usize, Vec<T>), Error> {
+    let mut width = None;
+    let mut cells = Vec::new();
+    let mut height = 0;
+    for (y, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let length = line.chars().count();
+        if *width.get_or_insert(length) != length {
+            return Err(Error::invalid(format!(
+                "expected {} cells, got {}",
+                width.unwrap_or_default(),
+                length
+            ))
+            .at_line(y + 1));
+        }
+        for (x, c) in line.chars().enumerate() {
+            cells.push(parse_cell(c).map_err(|e| e.at_line(y + 1).at_column(x + 1))?);
+        }
+        height += 1;
+    }
+    let width = width.ok_or_else(|| Error::invalid("empty grid"))?;
+    Ok((width, height, cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Cause;
+
+    #[test]
+    fn it_parses_lines() {
+        let parse = |s| parse_lines(s, |line| Ok(line.trim().parse::<u8>()?));
+        assert_eq!(Ok(vec![1, 2, 3]), parse("1\n2\n\n3\n"));
+        let error = parse("1\n2\nx\n").unwrap_err();
+        assert_eq!(Some(3), error.line);
+        assert!(matches!(error.cause, Cause::Invalid(_)));
+    }
+
+    #[test]
+    fn it_parses_grid() {
+        assert_eq!(
+            Ok((3, 2, vec![1, 2, 3, 4, 5, 6])),
+            parse_grid("123\n456\n", parse_digit)
+        );
+        let error = parse_grid("123\n4x6", parse_digit).unwrap_err();
+        assert_eq!((Some(2), Some(2)), (error.line, error.column));
+        assert_eq!(
+            Some(2),
+            parse_grid("123\n45", parse_digit).unwrap_err().line
+        );
+        assert!(parse_grid("", parse_digit).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    str::FromStr,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    day05::Ocean, day11::Octopuses, day13::Paper, day20::ImageEnhancer, day23_fast::Burrow,
+    day25::Region, error::Error,
+};
+
+/// A simulation that can be shown in the terminal, one step at a time.
+pub trait Simulation: Display {
+    /// Advances the simulation by one step. Returns false, and leaves the simulation as it is, once
+    /// there is nothing left to do.
+    fn step(&mut self) -> bool;
+}
+
+/// Frames that were computed up front, like the moves of a solution
+pub struct Frames<T> {
+    frames: Vec<T>,
+    current: usize,
+}
+
+impl<T> Frames<T> {
+    pub fn new(frames: Vec<T>) -> Frames<T> {
+        Frames { frames, current: 0 }
+    }
+}
+
+impl<T: Display> Display for Frames<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.frames.get(self.current) {
+            Some(frame) => write!(f, "{}", frame),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Display> Simulation for Frames<T> {
+    fn step(&mut self) -> bool {
+        if self.current + 1 < self.frames.len() {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The days that can be played back
+pub const DAYS: [u8; 6] = [5, 11, 13, 20, 23, 25];
+
+/// The simulation of the day, starting from the input
+pub fn get_simulation(day: u8, input: &str) -> Result<Box<dyn Simulation>, Error> {
+    Ok(match day {
+        5 => Box::new(input.parse::<Ocean>()?.without_vents()),
+        11 => Box::new(input.parse::<Octopuses>()?),
+        13 => Box::new(input.parse::<Paper>()?),
+        // As many enhancements as in part 2
+        20 => Box::new(input.parse::<ImageEnhancer>()?.with_iterations(50)),
+        23 => {
+            let burrow: Burrow = input.parse()?;
+            let plan = burrow
+                .solve()
+                .ok_or_else(|| Error::unsolvable("the amphipods cannot be organized"))?;
+            Box::new(Frames::new(plan.get_frames(&burrow)))
+        }
+        25 => Box::new(input.parse::<Region>()?),
+        _ => {
+            return Err(Error::unsupported(format!(
+                "there is no simulation to play back, try one of the days {:?}",
+                DAYS
+            )))
+        }
+    })
+}
+
+/// What the viewer can do while a simulation is playing, one command per line on stdin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    /// An empty line
+    TogglePause,
+    /// `s`, shows the next frame and pauses
+    Step,
+    /// `+`, halves the delay
+    Faster,
+    /// `-`, doubles the delay
+    Slower,
+    /// `q`
+    Quit,
+    /// Not typed, the delay is over
+    Next,
+}
+
+impl FromStr for Control {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Ok(Control::TogglePause),
+            "s" => Ok(Control::Step),
+            "+" => Ok(Control::Faster),
+            "-" => Ok(Control::Slower),
+            "q" => Ok(Control::Quit),
+            other => Err(Error::invalid(format!("unknown control '{}'", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackOptions {
+    /// Time between two frames
+    pub delay: Duration,
+    /// Start paused, so that every frame needs a step
+    pub paused: bool,
+    /// Stop after this many frames
+    pub frames: Option<usize>,
+}
+
+/// Draws the simulation frame by frame with ANSI escapes, until it is over, the number of frames is
+/// reached or the viewer quits. Returns the number of frames drawn.
+pub fn play(
+    simulation: &mut dyn Simulation,
+    options: &PlaybackOptions,
+    controls: &Receiver<Control>,
+    out: &mut impl Write,
+) -> io::Result<usize> {
+    let mut delay = options.delay;
+    let mut paused = options.paused;
+    let mut frame = 1;
+    // Clear the screen once, later frames only overwrite it to avoid flickering
+    write!(out, "\x1b[2J")?;
+    loop {
+        let last = options.frames.is_some_and(|frames| frame >= frames);
+        draw(simulation, frame, paused && !last, delay, out)?;
+        if last {
+            break;
+        }
+        let control = if paused {
+            // Without a way to step, there is nothing left to show
+            controls.recv().unwrap_or(Control::Quit)
+        } else {
+            match controls.recv_timeout(delay) {
+                Ok(control) => control,
+                Err(RecvTimeoutError::Timeout) => Control::Next,
+                Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(delay);
+                    Control::Next
+                }
+            }
+        };
+        match control {
+            Control::Next | Control::Step => {
+                paused |= control == Control::Step;
+                if !simulation.step() {
+                    break;
+                }
+                frame += 1;
+            }
+            Control::TogglePause => paused = !paused,
+            Control::Faster => delay /= 2,
+            Control::Slower => delay = (delay * 2).max(Duration::from_millis(1)),
+            Control::Quit => break,
+        }
+    }
+    writeln!(out)?;
+    out.flush()?;
+    Ok(frame)
+}
+
+fn draw(
+    simulation: &dyn Simulation,
+    frame: usize,
+    paused: bool,
+    delay: Duration,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    // Back to the top left, and clear what is left of longer lines of the previous frame
+    write!(out, "\x1b[H")?;
+    for line in simulation.to_string().lines() {
+        writeln!(out, "{}\x1b[K", line)?;
+    }
+    write!(
+        out,
+        "\x1b[KFrame {}{} ({:?} per frame). Enter: {}, s: step, +/-: speed, q: quit\x1b[J",
+        frame,
+        if paused { ", paused" } else { "" },
+        delay,
+        if paused { "resume" } else { "pause" }
+    )?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn options(frames: Option<usize>) -> PlaybackOptions {
+        PlaybackOptions {
+            delay: Duration::ZERO,
+            paused: false,
+            frames,
+        }
+    }
+
+    #[test]
+    fn it_plays_until_the_end() {
+        let (_sender, controls) = mpsc::channel();
+        let mut frames = Frames::new(vec!["first", "second", "third"]);
+        let mut out = Vec::new();
+        assert_eq!(
+            3,
+            play(&mut frames, &options(None), &controls, &mut out).unwrap()
+        );
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[2J\x1b[Hfirst\x1b[K\n"));
+        assert!(out.contains("\x1b[Hthird\x1b[K\n\x1b[KFrame 3 "));
+    }
+
+    #[test]
+    fn it_stops_after_frames() {
+        let (_sender, controls) = mpsc::channel();
+        let mut region: Region = "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v".parse().unwrap();
+        let mut out = Vec::new();
+        assert_eq!(
+            2,
+            play(&mut region, &options(Some(2)), &controls, &mut out).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_steps_when_paused() {
+        let (sender, controls) = mpsc::channel();
+        let mut frames = Frames::new(vec!["first", "second", "third"]);
+        let options = PlaybackOptions {
+            paused: true,
+            ..options(None)
+        };
+        sender.send(Control::Step).unwrap();
+        sender.send(Control::Quit).unwrap();
+        let mut out = Vec::new();
+        assert_eq!(2, play(&mut frames, &options, &controls, &mut out).unwrap());
+        assert!(String::from_utf8(out).unwrap().contains("Frame 2, paused"));
+        assert_eq!(Ok(Control::Faster), "+".parse());
+        assert!("x".parse::<Control>().is_err());
+    }
+
+    #[test]
+    fn it_plays_every_day() {
+        for day in DAYS {
+            let input = std::fs::read_to_string(format!("resources/day{:02}.txt", day)).unwrap();
+            let mut simulation = get_simulation(day, &input).unwrap();
+            assert!(simulation.step(), "day {} has no second frame", day);
+        }
+        assert!(get_simulation(1, "199").is_err());
+    }
+}
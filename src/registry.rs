@@ -0,0 +1,156 @@
+use crate::{
+    day01, day02, day03, day04, day05, day06, day06_fast, day07, day08, day09, day10, day11, day12,
+    day13, day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day23_fast, day24,
+    day25,
+    solution::{erase, DynSolution},
+};
+
+pub struct Variant {
+    pub name: &'static str,
+    pub solution: Box<dyn DynSolution>,
+}
+
+pub struct Day {
+    pub day: u8,
+    /// The first variant is the one that is used if none is requested.
+    pub variants: Vec<Variant>,
+}
+
+impl Day {
+    fn new(day: u8, solution: Box<dyn DynSolution>) -> Day {
+        Day {
+            day,
+            variants: vec![Variant {
+                name: "default",
+                solution,
+            }],
+        }
+    }
+
+    fn with_variants(day: u8, variants: Vec<Variant>) -> Day {
+        Day { day, variants }
+    }
+
+    pub fn get_variant(&self, name: Option<&str>) -> Option<&Variant> {
+        match name {
+            Some(name) => self.variants.iter().find(|variant| variant.name == name),
+            None => self.variants.first(),
+        }
+    }
+}
+
+pub fn get_days() -> Vec<Day> {
+    vec![
+        Day::new(1, erase::<day01::Day01>()),
+        Day::new(2, erase::<day02::Day02>()),
+        Day::new(3, erase::<day03::Day03>()),
+        Day::new(4, erase::<day04::Day04>()),
+        Day::new(5, erase::<day05::Day05>()),
+        Day::with_variants(
+            6,
+            vec![
+                Variant {
+                    name: "fast",
+                    solution: erase::<day06_fast::Day06>(),
+                },
+                Variant {
+                    name: "naive",
+                    solution: erase::<day06::Day06>(),
+                },
+            ],
+        ),
+        Day::new(7, erase::<day07::Day07>()),
+        Day::new(8, erase::<day08::Day08>()),
+        Day::new(9, erase::<day09::Day09>()),
+        Day::new(10, erase::<day10::Day10>()),
+        Day::new(11, erase::<day11::Day11>()),
+        Day::new(12, erase::<day12::Day12>()),
+        Day::new(13, erase::<day13::Day13>()),
+        Day::new(14, erase::<day14::Day14>()),
+        Day::new(15, erase::<day15::Day15>()),
+        Day::new(16, erase::<day16::Day16>()),
+        Day::new(17, erase::<day17::Day17>()),
+        Day::new(18, erase::<day18::Day18>()),
+        Day::new(19, erase::<day19::Day19>()),
+        Day::new(20, erase::<day20::Day20>()),
+        Day::new(21, erase::<day21::Day21>()),
+        Day::with_variants(
+            22,
+            vec![
+                Variant {
+                    name: "grid",
+                    solution: erase::<day22::Day22>(),
+                },
+                Variant {
+                    name: "fast",
+                    solution: erase::<day22::Day22Fast>(),
+                },
+            ],
+        ),
+        Day::with_variants(
+            23,
+            vec![
+                Variant {
+                    name: "fast",
+                    solution: erase::<day23_fast::Day23>(),
+                },
+                Variant {
+                    name: "naive",
+                    solution: erase::<day23::Day23>(),
+                },
+            ],
+        ),
+        Day::with_variants(
+            24,
+            vec![
+                Variant {
+                    name: "constraints",
+                    solution: erase::<day24::Day24>(),
+                },
+                Variant {
+                    name: "interpreted",
+                    solution: erase::<day24::Day24Interpreted>(),
+                },
+                Variant {
+                    name: "generated",
+                    solution: erase::<day24::Day24Generated>(),
+                },
+            ],
+        ),
+        Day::new(25, erase::<day25::Day25>()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_day(day: u8) -> Option<Day> {
+        get_days().into_iter().find(|d| d.day == day)
+    }
+
+    #[test]
+    fn it_registers_every_day_once() {
+        let days: Vec<u8> = get_days().iter().map(|day| day.day).collect();
+        assert_eq!((1..=25).collect::<Vec<u8>>(), days);
+    }
+
+    #[test]
+    fn it_finds_variants() {
+        let day = get_day(6).unwrap();
+        assert_eq!("fast", day.get_variant(None).unwrap().name);
+        assert_eq!("naive", day.get_variant(Some("naive")).unwrap().name);
+        assert!(day.get_variant(Some("slow")).is_none());
+    }
+
+    #[test]
+    fn it_solves_through_registry() {
+        let day = get_day(1).unwrap();
+        let solution = &day.get_variant(None).unwrap().solution;
+        let input = solution
+            .parse("199\n200\n208\n210\n200\n207\n240\n269\n260\n263")
+            .unwrap();
+        assert_eq!(Ok("7".to_owned()), solution.part1(input.as_ref()));
+        assert_eq!(Ok("5".to_owned()), solution.part2(input.as_ref()));
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    collections::BTreeMap,
+    io::BufRead,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    answers::Answers,
+    cli::{
+        DisassembleOptions, ExportOptions, GenerateOptions, PlayOptions, RunOptions, VerifyOptions,
+    },
+    day16, day23_generator,
+    error::Error,
+    image,
+    input::InputSource,
+    playback::{self, PlaybackOptions},
+    registry::{get_days, Day, Variant},
+};
+
+/// Prints the answers of the selected days. Fails if any day could not be solved, after all of
+/// them were run.
+pub fn run(options: &RunOptions) -> Result<(), String> {
+    let days = get_days();
+    let selected = select_variants(&days, &options.days, options.variant.as_deref())?;
+    let start = Instant::now();
+    let mut failed = 0;
+    in_order(
+        options.jobs,
+        &selected,
+        |(day, variant)| run_day(*day, variant, options),
+        |lines| {
+            if lines.iter().any(|line| line.is_err()) {
+                failed += 1;
+            }
+            for line in lines {
+                match line {
+                    Ok(line) => println!("{}", line),
+                    Err(line) => eprintln!("{}", line),
+                }
+            }
+        },
+    );
+    println!("Elapsed time: {}ms", start.elapsed().as_millis());
+    match failed {
+        0 => Ok(()),
+        1 => Err("1 day failed".to_owned()),
+        _ => Err(format!("{} days failed", failed)),
+    }
+}
+
+/// Picks the requested variant of every selected day.
+pub fn select_variants<'a>(
+    days: &'a [Day],
+    selected: &[u8],
+    variant: Option<&str>,
+) -> Result<Vec<(u8, &'a Variant)>, String> {
+    let days: Vec<&Day> = days
+        .iter()
+        .filter(|day| selected.contains(&day.day))
+        .collect();
+    if days.is_empty() {
+        return Err("No puzzle selected".to_owned());
+    }
+    if let Some(variant) = variant {
+        if days
+            .iter()
+            .all(|day| day.get_variant(Some(variant)).is_none())
+        {
+            return Err(format!(
+                "No selected day has a variant called '{}'",
+                variant
+            ));
+        }
+    }
+    days.into_iter()
+        .map(|day| {
+            // Days that don't know the requested variant fall back to their default.
+            day.get_variant(variant)
+                .or_else(|| day.get_variant(None))
+                .map(|variant| (day.day, variant))
+                .ok_or_else(|| format!("Day {} has no implementation", day.day))
+        })
+        .collect()
+}
+
+/// Runs `job` on every item with up to `jobs` worker threads, and passes the results to `emit`
+/// in the order of the items, each as soon as the ones before it are done.
+fn in_order<T, R>(jobs: usize, items: &[T], job: impl Fn(&T) -> R + Sync, mut emit: impl FnMut(R))
+where
+    T: Sync,
+    R: Send,
+{
+    if jobs <= 1 {
+        return items.iter().for_each(|item| emit(job(item)));
+    }
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            let (next, job, sender) = (&next, &job, sender.clone());
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                match items.get(index) {
+                    // The receiver only hangs up if `emit` panicked
+                    Some(item) => drop(sender.send((index, job(item)))),
+                    None => break,
+                }
+            });
+        }
+        drop(sender);
+        let mut done = BTreeMap::new();
+        let mut emitted = 0;
+        for (index, result) in receiver {
+            done.insert(index, result);
+            while let Some(result) = done.remove(&emitted) {
+                emit(result);
+                emitted += 1;
+            }
+        }
+    });
+}
+
+/// The lines printed for a day, errors go to stderr
+fn run_day(day: u8, variant: &Variant, options: &RunOptions) -> Vec<Result<String, String>> {
+    let input = match options
+        .input
+        .read(day)
+        .and_then(|input| variant.solution.parse(&input))
+    {
+        Ok(input) => input,
+        Err(e) => return vec![Err(e.in_day(day).to_string())],
+    };
+    let mut lines = Vec::new();
+    if options.part.one() {
+        lines.push(format_answer(
+            day,
+            1,
+            variant.solution.part1(input.as_ref()),
+        ));
+    }
+    if options.part.two() && !variant.solution.solves_part2() {
+        lines.push(Ok(format!("Day {} part 2: not supported", day)));
+    } else if options.part.two() {
+        lines.push(format_answer(
+            day,
+            2,
+            variant.solution.part2(input.as_ref()),
+        ));
+    }
+    lines
+}
+
+fn format_answer(day: u8, part: u8, answer: Result<String, Error>) -> Result<String, String> {
+    match answer {
+        // Multi-line answers like the ones of day 13 start on their own line
+        Ok(answer) if answer.contains('\n') => {
+            Ok(format!("Day {} part {}:\n{}", day, part, answer.trim_end()))
+        }
+        Ok(answer) => Ok(format!("Day {} part {}: {}", day, part, answer)),
+        Err(e) => Err(format!("Day {} part {}: {}", day, part, e)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Pass,
+    Fail,
+    /// There is no known answer to compare to
+    Missing,
+    /// The solution does not implement this part, e.g. naive variants. Errors of parts that are
+    /// implemented are failures, even if they say the input is not supported.
+    Unsupported,
+}
+
+fn check(expected: Option<&str>, answer: &Result<String, Error>) -> Status {
+    match (expected, answer) {
+        (Some(expected), Ok(answer)) if expected.trim_end() == answer.trim_end() => Status::Pass,
+        (None, Ok(_)) => Status::Missing,
+        _ => Status::Fail,
+    }
+}
+
+/// Runs the selected days on their inputs in `resources/` and compares the answers to the
+/// known-good ones. Returns whether none of them failed.
+pub fn verify(options: &VerifyOptions) -> Result<bool, String> {
+    let answers = Answers::load(&options.answers).map_err(|e| e.to_string())?;
+    let days = get_days();
+    let selected = select_variants(&days, &options.days, options.variant.as_deref())?;
+    let mut statuses = Vec::new();
+    let start = Instant::now();
+    in_order(
+        options.jobs,
+        &selected,
+        |(day, variant)| verify_day(*day, variant, &answers),
+        |(report, day_statuses)| {
+            print!("{}", report);
+            statuses.extend(day_statuses);
+        },
+    );
+    let count = |status| statuses.iter().filter(|s| **s == status).count();
+    println!(
+        "{} passed, {} failed, {} missing, {} not supported. Elapsed time: {}ms",
+        count(Status::Pass),
+        count(Status::Fail),
+        count(Status::Missing),
+        count(Status::Unsupported),
+        start.elapsed().as_millis()
+    );
+    Ok(count(Status::Fail) == 0)
+}
+
+/// Checks both parts of a day, returns what to print and their statuses
+fn verify_day(day: u8, variant: &Variant, answers: &Answers) -> (String, Vec<Status>) {
+    let parse_start = Instant::now();
+    let input = match InputSource::Resources
+        .read(day)
+        .and_then(|input| variant.solution.parse(&input))
+    {
+        Ok(input) => input,
+        Err(e) => {
+            let report = format!("Day {:>2} input: FAIL, {}\n", day, e.in_day(day));
+            return (report, vec![Status::Fail, Status::Fail]);
+        }
+    };
+    let mut report = format!(
+        "Day {:>2} input: parsed ({:.1?})\n",
+        day,
+        parse_start.elapsed()
+    );
+    let mut statuses = Vec::new();
+    for part in [1, 2] {
+        let part_start = Instant::now();
+        let answer = match part {
+            1 => variant.solution.part1(input.as_ref()),
+            _ => variant.solution.part2(input.as_ref()),
+        };
+        let expected = answers.get(day, part);
+        let status = match part == 2 && !variant.solution.solves_part2() {
+            true => Status::Unsupported,
+            false => check(expected, &answer),
+        };
+        report += &format_status(day, part, status, expected, &answer, part_start.elapsed());
+        statuses.push(status);
+    }
+    (report, statuses)
+}
+
+fn format_status(
+    day: u8,
+    part: u8,
+    status: Status,
+    expected: Option<&str>,
+    answer: &Result<String, Error>,
+    elapsed: Duration,
+) -> String {
+    // Multi-line answers like the ones of day 13 start on their own line
+    let show = |value: &str| match value.contains('\n') {
+        true => format!("\n{}", value.trim_end()),
+        false => value.to_owned(),
+    };
+    let answer = match answer {
+        Ok(answer) => show(answer),
+        Err(e) => e.to_string(),
+    };
+    let prefix = format!("Day {:>2} part {}:", day, part);
+    match status {
+        Status::Pass => format!("{} pass ({:.1?})\n", prefix, elapsed),
+        Status::Fail => format!(
+            "{} FAIL ({:.1?})\nexpected: {}\ngot: {}\n",
+            prefix,
+            elapsed,
+            expected.map(show).unwrap_or_default(),
+            answer
+        ),
+        Status::Missing => format!("{} missing ({:.1?}), got {}\n", prefix, elapsed, answer),
+        Status::Unsupported => format!("{} not supported\n", prefix),
+    }
+}
+
+/// Prints random day 23 burrows with their optimal energy
+pub fn generate(options: &GenerateOptions) -> Result<(), String> {
+    for seed in (options.seed..).take(options.count) {
+        let puzzle = day23_generator::generate(options.rooms, options.depth, seed)
+            .map_err(|e| e.in_day(23).to_string())?;
+        match puzzle.energy {
+            Some(energy) => println!("Seed {}, optimal energy: {}", seed, energy),
+            None => println!("Seed {}, the amphipods cannot be organized", seed),
+        }
+        println!("{}\n", puzzle.map);
+    }
+    Ok(())
+}
+
+/// Lists the packets of a transmission, then prints it as an expression and an S-expression
+pub fn disassemble(options: &DisassembleOptions) -> Result<(), String> {
+    let message = match &options.message {
+        Some(message) => message.clone(),
+        None => options
+            .input
+            .read(16)
+            .map_err(|e| e.in_day(16).to_string())?,
+    };
+    let (packet, headers) = day16::disassemble(&message).map_err(|e| e.to_string())?;
+    for header in headers {
+        println!("{}", header);
+    }
+    println!("\n{}\n{}", packet, packet.to_s_expression());
+    Ok(())
+}
+
+/// Animates the simulation of a day, controlled by lines typed on stdin
+pub fn play(options: &PlayOptions) -> Result<(), String> {
+    let mut simulation = options
+        .input
+        .read(options.day)
+        .and_then(|input| playback::get_simulation(options.day, &input))
+        .map_err(|e| e.in_day(options.day).to_string())?;
+    let (sender, controls) = mpsc::channel();
+    // Stops at the end of stdin, or when the playback is over and nobody listens anymore
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+            if let Ok(control) = line.parse() {
+                if sender.send(control).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    let playback = PlaybackOptions {
+        delay: Duration::from_millis(options.delay),
+        paused: options.paused,
+        frames: options.frames,
+    };
+    playback::play(
+        simulation.as_mut(),
+        &playback,
+        &controls,
+        &mut std::io::stdout().lock(),
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/// Writes the pictures of a day in the format of the extension of the output path
+pub fn export(options: &ExportOptions) -> Result<(), String> {
+    let frames: Vec<image::Image> = options
+        .input
+        .read(options.day)
+        .and_then(|input| image::get_frames(options.day, &input, options.frames))
+        .map_err(|e| e.in_day(options.day).to_string())?
+        .iter()
+        .map(|frame| frame.scaled(options.scale))
+        .collect();
+    let output = &options.output;
+    let extension = output.extension().and_then(|extension| extension.to_str());
+    let encode = match extension {
+        Some("gif") => {
+            let delay = u16::try_from(options.delay / 10).unwrap_or(u16::MAX);
+            let gif = image::to_gif(&frames, delay).map_err(|e| e.to_string())?;
+            return write_file(output, &gif);
+        }
+        Some("png") => image::Image::to_png,
+        Some("ppm") => image::Image::to_ppm,
+        _ => {
+            return Err(format!(
+                "Cannot tell the format of '{}', expected .gif, .png or .ppm",
+                output.display()
+            ))
+        }
+    };
+    if let [frame] = frames.as_slice() {
+        return write_file(output, &encode(frame));
+    }
+    // One file per frame, numbered like frames-000.png
+    let stem = output.with_extension("");
+    for (index, frame) in frames.iter().enumerate() {
+        let path = format!(
+            "{}-{:03}.{}",
+            stem.display(),
+            index,
+            extension.unwrap_or_default()
+        );
+        write_file(path.as_ref(), &encode(frame))?;
+    }
+    Ok(())
+}
+
+fn write_file(path: &std::path::Path, data: &[u8]) -> Result<(), String> {
+    std::fs::write(path, data).map_err(|e| format!("{}: {}", path.display(), e))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+pub fn list() {
+    for day in get_days() {
+        println!(
+            "Day {:>2}: {}",
+            day.day,
+            day.variants
+                .iter()
+                .map(|variant| variant.name)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_emits_results_in_order() {
+        let items: Vec<u64> = (0..20).collect();
+        for jobs in [1, 4] {
+            let mut emitted = Vec::new();
+            in_order(
+                jobs,
+                &items,
+                |item| {
+                    // Later items finish first
+                    thread::sleep(Duration::from_millis(20 - item));
+                    item * 2
+                },
+                |result| emitted.push(result),
+            );
+            assert_eq!(
+                items.iter().map(|item| item * 2).collect::<Vec<u64>>(),
+                emitted
+            );
+        }
+    }
+
+    #[test]
+    fn it_checks_answers() {
+        assert_eq!(Status::Pass, check(Some("42"), &Ok("42".to_owned())));
+        assert_eq!(
+            Status::Pass,
+            check(Some("#.\n.#"), &Ok("#.\n.#\n".to_owned()))
+        );
+        assert_eq!(Status::Fail, check(Some("42"), &Ok("43".to_owned())));
+        assert_eq!(
+            Status::Fail,
+            check(Some("42"), &Err(Error::unsolvable("no path")))
+        );
+        assert_eq!(Status::Missing, check(None, &Ok("42".to_owned())));
+        // Solutions that run but reject the input fail
+        assert_eq!(
+            Status::Fail,
+            check(Some("42"), &Err(Error::unsupported("naive")))
+        );
+    }
+}
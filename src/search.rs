@@ -0,0 +1,240 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+/// What a step costs. Sums saturate, so that huge costs compare as the most expensive instead of
+/// overflowing.
+pub trait Cost: Copy + Ord {
+    const ZERO: Self;
+
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty),*) => {
+        $(
+            impl Cost for $t {
+                const ZERO: Self = 0;
+
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_cost!(u8, u16, u32, u64, u128, usize);
+
+/// The cheapest way from the start to a goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub cost: C,
+    /// From the start to the goal, both included
+    pub nodes: Vec<N>,
+}
+
+/// Dijkstra's algorithm and A* over nodes that are discovered while searching. The search keeps
+/// its buffers between runs, so searching repeatedly does not allocate them again.
+pub struct Search<N, C> {
+    nodes: Vec<N>,
+    indices: HashMap<N, usize>,
+    costs: Vec<C>,
+    /// The node every node was reached from on its cheapest known way
+    previous: Vec<Option<usize>>,
+    /// The estimated total cost, the cost so far and the node
+    queue: BinaryHeap<Reverse<(C, C, usize)>>,
+}
+
+impl<N, C: Ord> Default for Search<N, C> {
+    fn default() -> Self {
+        Search {
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            costs: Vec::new(),
+            previous: Vec::new(),
+            queue: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash, C: Cost> Search<N, C> {
+    /// The cheapest path from `start` to any node that `is_goal`, `None` if there is none.
+    /// `neighbours` lists the nodes one step away, with the cost of that step.
+    pub fn dijkstra<I>(
+        &mut self,
+        start: N,
+        neighbours: impl FnMut(&N) -> I,
+        is_goal: impl FnMut(&N) -> bool,
+    ) -> Option<Path<N, C>>
+    where
+        I: IntoIterator<Item = (N, C)>,
+    {
+        self.a_star(start, neighbours, |_| C::ZERO, is_goal)
+    }
+
+    /// Like [Search::dijkstra], but looks at the nodes that seem closest to a goal first. The
+    /// `heuristic` must never overestimate the cost to the nearest goal, and must not drop by more
+    /// than the cost of a step, or the path might not be the cheapest.
+    pub fn a_star<I>(
+        &mut self,
+        start: N,
+        mut neighbours: impl FnMut(&N) -> I,
+        mut heuristic: impl FnMut(&N) -> C,
+        mut is_goal: impl FnMut(&N) -> bool,
+    ) -> Option<Path<N, C>>
+    where
+        I: IntoIterator<Item = (N, C)>,
+    {
+        self.nodes.clear();
+        self.indices.clear();
+        self.costs.clear();
+        self.previous.clear();
+        self.queue.clear();
+        let estimate = heuristic(&start);
+        self.visit(start, C::ZERO, estimate, None);
+        while let Some(Reverse((_, cost, index))) = self.queue.pop() {
+            if cost > self.costs[index] {
+                // Reached on a cheaper way since this entry was queued
+                continue;
+            }
+            let node = self.nodes[index].clone();
+            if is_goal(&node) {
+                return Some(self.get_path(index));
+            }
+            for (next, step) in neighbours(&node) {
+                let estimate = heuristic(&next);
+                self.visit(next, cost.saturating_add(step), estimate, Some(index));
+            }
+        }
+        None
+    }
+
+    fn visit(&mut self, node: N, cost: C, estimate: C, from: Option<usize>) {
+        let Search {
+            nodes,
+            indices,
+            costs,
+            previous,
+            queue,
+        } = self;
+        let index = match indices.get(&node) {
+            Some(&index) => index,
+            None => {
+                indices.insert(node.clone(), nodes.len());
+                nodes.push(node);
+                costs.push(cost);
+                previous.push(from);
+                queue.push(Reverse((
+                    cost.saturating_add(estimate),
+                    cost,
+                    nodes.len() - 1,
+                )));
+                return;
+            }
+        };
+        if cost < costs[index] {
+            costs[index] = cost;
+            previous[index] = from;
+            queue.push(Reverse((cost.saturating_add(estimate), cost, index)));
+        }
+    }
+
+    fn get_path(&self, mut index: usize) -> Path<N, C> {
+        let cost = self.costs[index];
+        let mut nodes = vec![self.nodes[index].clone()];
+        while let Some(previous) = self.previous[index] {
+            nodes.push(self.nodes[previous].clone());
+            index = previous;
+        }
+        nodes.reverse();
+        Path { cost, nodes }
+    }
+}
+
+/// [Search::dijkstra] with new buffers
+pub fn dijkstra<N, C, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    Search::default().dijkstra(start, neighbours, is_goal)
+}
+
+/// [Search::a_star] with new buffers
+pub fn a_star<N, C, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    Search::default().a_star(start, neighbours, heuristic, is_goal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steps of +1 cost 2, steps of +3 cost 5, so the fewest steps are not always the cheapest
+    fn neighbours(&n: &u32) -> Vec<(u32, u32)> {
+        vec![(n + 1, 2), (n + 3, 5)]
+    }
+
+    /// Two steps of +3 and one of +1, in any order
+    fn assert_cheapest_to_7(path: Option<Path<u32, u32>>) {
+        let path = path.unwrap();
+        assert_eq!(12, path.cost);
+        assert_eq!(
+            (4, Some(&0), Some(&7)),
+            (path.nodes.len(), path.nodes.first(), path.nodes.last())
+        );
+    }
+
+    #[test]
+    fn it_finds_the_cheapest_path() {
+        assert_cheapest_to_7(dijkstra(0, neighbours, |&n| n == 7));
+        assert_eq!(
+            Some(0),
+            dijkstra(4, neighbours, |&n| n == 4).map(|p| p.cost)
+        );
+        // Without a goal, the search ends once there is nothing left to reach
+        let bounded = |&n: &u32| neighbours(&n).into_iter().filter(|&(n, _)| n < 10);
+        assert_eq!(None, dijkstra(0, bounded, |&n| n == 20));
+    }
+
+    #[test]
+    fn it_finds_the_same_path_with_a_heuristic() {
+        // Every 3 steps cost at least 5
+        let heuristic = |&n: &u32| 7u32.saturating_sub(n) * 5 / 3;
+        let mut search = Search::default();
+        assert_cheapest_to_7(search.a_star(0, neighbours, heuristic, |&n| n == 7));
+        assert_cheapest_to_7(search.a_star(0, neighbours, heuristic, |&n| n == 7));
+        assert_eq!(
+            Some(0),
+            search
+                .a_star(7, neighbours, heuristic, |&n| n == 7)
+                .map(|p| p.cost)
+        );
+    }
+
+    #[test]
+    fn it_saturates_costs() {
+        let expensive = |&n: &u8| [(n + 1, u64::MAX), (n + 2, u64::MAX)];
+        assert_eq!(
+            Some(u64::MAX),
+            dijkstra(0, expensive, |&n| n == 4).map(|p| p.cost)
+        );
+    }
+}
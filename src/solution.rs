@@ -0,0 +1,135 @@
+use std::{any::Any, fmt::Display, marker::PhantomData};
+
+use crate::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+    Both,
+}
+
+impl Part {
+    pub fn one(self) -> bool {
+        self != Part::Two
+    }
+
+    pub fn two(self) -> bool {
+        self != Part::One
+    }
+}
+
+/// A puzzle solution, split into parsing the input and solving the two parts on the parsed input.
+pub trait Solution {
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// Whether [Solution::part2] is implemented at all. Without it, part 2 is reported as not
+    /// supported instead of failed.
+    const SOLVES_PART2: bool = true;
+
+    fn parse(input: &str) -> Result<Self::Input, Error>;
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error>;
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error>;
+}
+
+/// Object safe counterpart of [Solution], so that different days can be stored side by side.
+/// The parsed input is passed around as [Any] and the answers are rendered to strings.
+pub trait DynSolution: Send + Sync {
+    fn parse(&self, input: &str) -> Result<Box<dyn Any + Send>, Error>;
+    fn part1(&self, input: &dyn Any) -> Result<String, Error>;
+    fn part2(&self, input: &dyn Any) -> Result<String, Error>;
+    fn solves_part2(&self) -> bool;
+}
+
+struct Erased<S>(PhantomData<fn() -> S>);
+
+impl<S> Erased<S>
+where
+    S: Solution,
+    S::Input: 'static,
+{
+    fn downcast(input: &dyn Any) -> Result<&S::Input, Error> {
+        input
+            .downcast_ref()
+            .ok_or_else(|| Error::unsupported("the input was parsed by a different solution"))
+    }
+}
+
+impl<S> DynSolution for Erased<S>
+where
+    S: Solution,
+    S::Input: Send + 'static,
+{
+    fn parse(&self, input: &str) -> Result<Box<dyn Any + Send>, Error> {
+        S::parse(input).map(|input| Box::new(input) as Box<dyn Any + Send>)
+    }
+
+    fn part1(&self, input: &dyn Any) -> Result<String, Error> {
+        S::part1(Self::downcast(input)?).map(|answer| answer.to_string())
+    }
+
+    fn part2(&self, input: &dyn Any) -> Result<String, Error> {
+        S::part2(Self::downcast(input)?).map(|answer| answer.to_string())
+    }
+
+    fn solves_part2(&self) -> bool {
+        S::SOLVES_PART2
+    }
+}
+
+pub fn erase<S>() -> Box<dyn DynSolution>
+where
+    S: Solution + 'static,
+    S::Input: Send + 'static,
+{
+    Box::new(Erased::<S>(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Input = Vec<u32>;
+        type Answer1 = u32;
+        type Answer2 = String;
+
+        const SOLVES_PART2: bool = false;
+
+        fn parse(input: &str) -> Result<Self::Input, Error> {
+            input
+                .split(',')
+                .map(|v| Ok(v.parse()?))
+                .collect()
+        }
+
+        fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+            Ok(input.iter().sum())
+        }
+
+        fn part2(_input: &Self::Input) -> Result<Self::Answer2, Error> {
+            Err(Error::unsupported("there is no part 2"))
+        }
+    }
+
+    #[test]
+    fn it_erases_solution() {
+        let solution = erase::<Sum>();
+        let input = solution.parse("1,2,3").unwrap();
+        assert_eq!(Ok("6".to_owned()), solution.part1(input.as_ref()));
+        assert!(solution.part2(input.as_ref()).is_err());
+        assert!(!solution.solves_part2());
+        assert!(solution.parse("1,a").is_err());
+    }
+
+    #[test]
+    fn it_rejects_foreign_input() {
+        let solution = erase::<Sum>();
+        let input: Box<dyn Any + Send> = Box::new("1,2,3");
+        assert!(solution.part1(input.as_ref()).is_err());
+    }
+}